@@ -0,0 +1,155 @@
+use clap::{Parser, Subcommand};
+use easy_fs::{BlockDevice, EasyFileSystem, Inode, BLOCK_SZ};
+use std::{
+    fs::{self, File, OpenOptions, read_dir},
+    io::{self, Read, Result, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex}
+};
+
+/// Size of an image in blocks, which is 16 MiB.
+const TOTAL_BLOCKS: u32 = 16 * 2048;
+/// Blocks of the inode bitmap, each of which tracks 4096 inodes.
+const INODE_BITMAP_BLOCKS: u32 = 1;
+
+/// An image file on the host, which is used as a block device.
+struct BlockFile(Mutex<File>);
+
+impl BlockDevice for BlockFile {
+    fn read_block(&self, block_id: usize, buf: &mut [u8]) {
+        let mut file = self.0.lock().unwrap();
+        file.seek(SeekFrom::Start((block_id * BLOCK_SZ) as u64))
+            .expect("Error when seeking.");
+        file.read_exact(buf).expect("Error when reading a block.");
+    }
+
+    fn write_block(&self, block_id: usize, buf: &[u8]) {
+        let mut file = self.0.lock().unwrap();
+        file.seek(SeekFrom::Start((block_id * BLOCK_SZ) as u64))
+            .expect("Error when seeking.");
+        file.write_all(buf).expect("Error when writing a block.");
+    }
+}
+
+#[derive(Parser)]
+#[command(about = "Creates and inspects easy-fs images.")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Creates an image with the compiled apps, and optionally some data files.
+    Create {
+        /// Directory of app sources, whose file names are the app names.
+        #[arg(short, long)]
+        source: PathBuf,
+        /// Directory of the compiled app ELF files.
+        #[arg(short, long)]
+        target: PathBuf,
+        /// Data file to be added to the root directory, which may be repeated.
+        #[arg(short, long)]
+        data: Vec<PathBuf>,
+        #[arg(short, long, default_value = "fs.img")]
+        image: PathBuf
+    },
+    /// Lists the root directory of an image.
+    Ls {
+        #[arg(short, long, default_value = "fs.img")]
+        image: PathBuf
+    },
+    /// Prints a file in the root directory of an image.
+    Cat {
+        name: String,
+        #[arg(short, long, default_value = "fs.img")]
+        image: PathBuf
+    }
+}
+
+fn main() -> Result<()> {
+    match Cli::parse().command {
+        Command::Create { source, target, data, image } => create(&source, &target, &data, &image),
+        Command::Ls { image } => {
+            for name in open(&image)?.ls() {
+                println!("{}", name);
+            }
+            Ok(())
+        },
+        Command::Cat { name, image } => {
+            let inode = open(&image)?
+                .find(&name)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{} is not found.", name)))?;
+            io::stdout().write_all(&read_all(&inode))
+        }
+    }
+}
+
+/// Formats a new image, and copies the apps and data files into its root directory.
+fn create(source: &Path, target: &Path, data: &[PathBuf], image: &Path) -> Result<()> {
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(image)?;
+    file.set_len(TOTAL_BLOCKS as u64 * BLOCK_SZ as u64)?;
+    let efs = EasyFileSystem::create(Arc::new(BlockFile(Mutex::new(file))), TOTAL_BLOCKS, INODE_BITMAP_BLOCKS);
+    let root_inode = EasyFileSystem::root_inode(&efs);
+
+    let mut apps: Vec<_> = read_dir(source)?
+        .map(|dir_entry| {
+            let path = dir_entry?.path();
+            let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+            Ok(name)
+        })
+        .collect::<Result<_>>()?;
+    apps.sort();
+
+    for app in apps.iter() {
+        add_file(&root_inode, app, &target.join(app))?;
+        println!("app: {}", app);
+    }
+    for path in data.iter() {
+        let name = path.file_name().unwrap().to_string_lossy();
+        add_file(&root_inode, &name, path)?;
+        println!("data: {}", name);
+    }
+
+    Ok(())
+}
+
+fn open(image: &Path) -> Result<Arc<Inode>> {
+    let file = OpenOptions::new().read(true).write(true).open(image)?;
+    let efs = EasyFileSystem::open(Arc::new(BlockFile(Mutex::new(file))))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Not an easy-fs image."))?;
+
+    Ok(EasyFileSystem::root_inode(&efs))
+}
+
+/// Copies the host file at `path` into `dir` as `name`.
+fn add_file(dir: &Inode, name: &str, path: &Path) -> Result<()> {
+    let content = fs::read(path)?;
+    let inode = dir
+        .create(name)
+        .map_err(|err| io::Error::other(format!("Failed to create {}: {:?}.", name, err)))?;
+    if inode.write_at(0, &content) != content.len() {
+        return Err(io::Error::other(format!("No space left for {}.", name)));
+    }
+
+    Ok(())
+}
+
+fn read_all(inode: &Inode) -> Vec<u8> {
+    let mut content = Vec::new();
+    let mut buffer = [0u8; BLOCK_SZ];
+    loop {
+        let len = inode.read_at(content.len(), &mut buffer);
+        if len == 0 {
+            break;
+        }
+        content.extend_from_slice(&buffer[..len]);
+    }
+
+    content
+}
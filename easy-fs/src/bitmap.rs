@@ -0,0 +1,64 @@
+use alloc::sync::Arc;
+use super::{
+    BlockDevice, BLOCK_SZ,
+    block_cache::get_block_cache
+};
+
+/// A bitmap block, viewed as groups of 64 bits.
+type BitmapBlock = [u64; BLOCK_SZ / 8];
+
+/// Number of bits in a block.
+pub const BLOCK_BITS: usize = BLOCK_SZ * 8;
+
+/// A bitmap over consecutive blocks, where each set bit marks an allocated object.
+pub struct Bitmap {
+    start_block_id: usize,
+    blocks: usize
+}
+
+impl Bitmap {
+    pub fn new(start_block_id: usize, blocks: usize) -> Self {
+        Self { start_block_id, blocks }
+    }
+
+    /// Sets the lowest clear bit, and returns its index, or `None` if all bits are set.
+    pub fn alloc(&self, block_device: &Arc<dyn BlockDevice>) -> Option<usize> {
+        for block_id in 0..self.blocks {
+            let bit = get_block_cache(self.start_block_id + block_id, Arc::clone(block_device))
+                .lock()
+                .modify(0, |bitmap_block: &mut BitmapBlock| {
+                    let (bits64_pos, bits64) = bitmap_block
+                        .iter_mut()
+                        .enumerate()
+                        .find(|(_, bits64)| **bits64 != u64::MAX)?;
+                    let inner_pos = bits64.trailing_ones() as usize;
+                    *bits64 |= 1 << inner_pos;
+
+                    Some(block_id * BLOCK_BITS + bits64_pos * 64 + inner_pos)
+                });
+            if bit.is_some() {
+                return bit;
+            }
+        }
+
+        None
+    }
+
+    /// Clears a bit, which must be set.
+    pub fn dealloc(&self, block_device: &Arc<dyn BlockDevice>, bit: usize) {
+        let block_id = bit / BLOCK_BITS;
+        let bits64_pos = bit % BLOCK_BITS / 64;
+        let inner_pos = bit % 64;
+        get_block_cache(self.start_block_id + block_id, Arc::clone(block_device))
+            .lock()
+            .modify(0, |bitmap_block: &mut BitmapBlock| {
+                assert!(bitmap_block[bits64_pos] & (1 << inner_pos) != 0, "Bit {} is not allocated.", bit);
+                bitmap_block[bits64_pos] &= !(1 << inner_pos);
+            });
+    }
+
+    /// Returns the number of bits, which bounds the number of objects.
+    pub fn maximum(&self) -> usize {
+        self.blocks * BLOCK_BITS
+    }
+}
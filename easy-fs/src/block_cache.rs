@@ -0,0 +1,139 @@
+use alloc::{collections::VecDeque, sync::Arc};
+use lazy_static::lazy_static;
+use spin::Mutex;
+use super::{BlockDevice, BLOCK_SZ};
+
+/// Most blocks cached in memory at the same time.
+const BLOCK_CACHE_SIZE: usize = 16;
+
+/// Content of a block, which is aligned for the on-disk structures cast from it.
+/// It is aligned to its size as well, so that it never crosses a page for DMA.
+#[repr(C, align(512))]
+struct BlockBuffer([u8; BLOCK_SZ]);
+
+/// A block cached in memory, which is written back when it is dropped or synchronized.
+pub struct BlockCache {
+    cache: BlockBuffer,
+    block_id: usize,
+    block_device: Arc<dyn BlockDevice>,
+    modified: bool
+}
+
+impl BlockCache {
+    /// Loads a block from the device.
+    pub fn new(block_id: usize, block_device: Arc<dyn BlockDevice>) -> Self {
+        let mut cache = BlockBuffer([0; BLOCK_SZ]);
+        block_device.read_block(block_id, &mut cache.0);
+
+        Self {
+            cache,
+            block_id,
+            block_device,
+            modified: false
+        }
+    }
+
+    fn addr_of_offset(&self, offset: usize) -> usize {
+        &self.cache.0[offset] as *const u8 as usize
+    }
+
+    fn get_ref<T: Sized>(&self, offset: usize) -> &T {
+        assert!(offset + size_of::<T>() <= BLOCK_SZ, "Value at {} exceeds the block.", offset);
+        assert!(offset.is_multiple_of(align_of::<T>()), "Value at {} is misaligned.", offset);
+
+        unsafe { &*(self.addr_of_offset(offset) as *const T) }
+    }
+
+    fn get_mut<T: Sized>(&mut self, offset: usize) -> &mut T {
+        assert!(offset + size_of::<T>() <= BLOCK_SZ, "Value at {} exceeds the block.", offset);
+        assert!(offset.is_multiple_of(align_of::<T>()), "Value at {} is misaligned.", offset);
+        self.modified = true;
+
+        unsafe { &mut *(self.addr_of_offset(offset) as *mut T) }
+    }
+
+    /// Reads the value of type `T` at `offset` of the block through `f`.
+    pub fn read<T, V>(&self, offset: usize, f: impl FnOnce(&T) -> V) -> V {
+        f(self.get_ref(offset))
+    }
+
+    /// Modifies the value of type `T` at `offset` of the block through `f`.
+    pub fn modify<T, V>(&mut self, offset: usize, f: impl FnOnce(&mut T) -> V) -> V {
+        f(self.get_mut(offset))
+    }
+
+    /// Writes the block back to the device if it is modified.
+    pub fn sync(&mut self) {
+        if self.modified {
+            self.modified = false;
+            self.block_device.write_block(self.block_id, &self.cache.0);
+        }
+    }
+}
+
+impl Drop for BlockCache {
+    fn drop(&mut self) {
+        self.sync();
+    }
+}
+
+/// Identifies a block by the address of its device and its id, so that blocks with the same id
+/// on different devices are cached apart. The address stays valid while the block is cached,
+/// as the cache holds the device.
+type BlockKey = (usize, usize);
+
+fn block_key(block_id: usize, block_device: &Arc<dyn BlockDevice>) -> BlockKey {
+    (Arc::as_ptr(block_device) as *const () as usize, block_id)
+}
+
+/// Caches recently used blocks, and evicts the oldest unused one when it is full.
+pub struct BlockCacheManager {
+    queue: VecDeque<(BlockKey, Arc<Mutex<BlockCache>>)>
+}
+
+impl BlockCacheManager {
+    pub fn new() -> Self {
+        Self { queue: VecDeque::new() }
+    }
+
+    pub fn get_block_cache(
+        &mut self,
+        block_id: usize,
+        block_device: Arc<dyn BlockDevice>
+    ) -> Arc<Mutex<BlockCache>> {
+        let key = block_key(block_id, &block_device);
+        if let Some((_, cache)) = self.queue.iter().find(|(cached_key, _)| *cached_key == key) {
+            return Arc::clone(cache);
+        }
+
+        if self.queue.len() == BLOCK_CACHE_SIZE {
+            // Caches still referenced elsewhere cannot be evicted.
+            let idx = self.queue
+                .iter()
+                .position(|(_, cache)| Arc::strong_count(cache) == 1)
+                .expect("Run out of block caches.");
+            self.queue.remove(idx);
+        }
+        let cache = Arc::new(Mutex::new(BlockCache::new(block_id, block_device)));
+        self.queue.push_back((key, Arc::clone(&cache)));
+
+        cache
+    }
+}
+
+lazy_static! {
+    pub static ref BLOCK_CACHE_MANAGER: Mutex<BlockCacheManager> = Mutex::new(BlockCacheManager::new());
+}
+
+/// Returns the cache of given block, which is loaded from the device if it is not cached yet.
+pub fn get_block_cache(block_id: usize, block_device: Arc<dyn BlockDevice>) -> Arc<Mutex<BlockCache>> {
+    BLOCK_CACHE_MANAGER.lock().get_block_cache(block_id, block_device)
+}
+
+/// Writes all modified blocks back to their devices.
+pub fn block_cache_sync_all() {
+    let manager = BLOCK_CACHE_MANAGER.lock();
+    for (_, cache) in manager.queue.iter() {
+        cache.lock().sync();
+    }
+}
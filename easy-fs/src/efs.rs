@@ -0,0 +1,306 @@
+use alloc::{
+    collections::BTreeMap,
+    sync::{Arc, Weak},
+    vec::Vec
+};
+use spin::Mutex;
+use super::{
+    BlockDevice, FsError, FsResult, Inode, BLOCK_SZ,
+    bitmap::{Bitmap, BLOCK_BITS},
+    block_cache::{BlockCache, get_block_cache, block_cache_sync_all},
+    layout::{DataBlock, DirEntry, DiskInode, DiskInodeType, SuperBlock, DIRENT_SZ}
+};
+
+/// Number of inodes in a block of the inode area.
+const INODES_PER_BLOCK: u32 = (BLOCK_SZ / size_of::<DiskInode>()) as u32;
+
+/// A mounted easy-fs, which allocates inodes and data blocks on the device.
+pub struct EasyFileSystem {
+    pub block_device: Arc<dyn BlockDevice>,
+    inode_bitmap: Bitmap,
+    data_bitmap: Bitmap,
+    inode_area_start_block: u32,
+    data_area_start_block: u32,
+    data_area_blocks: u32,
+    /// Handles of the inodes in use, so that an inode has at most one handle at a time.
+    open_inodes: BTreeMap<u32, Weak<Inode>>
+}
+
+impl EasyFileSystem {
+    /// Formats a device of `total_blocks` blocks, where `inode_bitmap_blocks` blocks of bitmap
+    /// bound the number of inodes, and returns the filesystem with an empty root directory.
+    pub fn create(
+        block_device: Arc<dyn BlockDevice>,
+        total_blocks: u32,
+        inode_bitmap_blocks: u32
+    ) -> Arc<Mutex<Self>> {
+        let inode_bitmap = Bitmap::new(1, inode_bitmap_blocks as usize);
+        let inode_num = inode_bitmap.maximum() as u32;
+        let inode_area_blocks = inode_num.div_ceil(INODES_PER_BLOCK);
+        let inode_total_blocks = inode_bitmap_blocks + inode_area_blocks;
+        let data_total_blocks = total_blocks - 1 - inode_total_blocks;
+        // Each block of the data bitmap tracks `BLOCK_BITS` data blocks.
+        let data_bitmap_blocks = data_total_blocks.div_ceil(BLOCK_BITS as u32 + 1);
+        let data_area_blocks = data_total_blocks - data_bitmap_blocks;
+        let data_bitmap = Bitmap::new((1 + inode_total_blocks) as usize, data_bitmap_blocks as usize);
+        let mut efs = Self {
+            block_device: Arc::clone(&block_device),
+            inode_bitmap,
+            data_bitmap,
+            inode_area_start_block: 1 + inode_bitmap_blocks,
+            data_area_start_block: 1 + inode_total_blocks + data_bitmap_blocks,
+            data_area_blocks,
+            open_inodes: BTreeMap::new()
+        };
+
+        for block_id in 0..total_blocks {
+            get_block_cache(block_id as usize, Arc::clone(&block_device))
+                .lock()
+                .modify(0, |data: &mut DataBlock| data.fill(0));
+        }
+        get_block_cache(0, Arc::clone(&block_device))
+            .lock()
+            .modify(0, |super_block: &mut SuperBlock| {
+                super_block.initialize(
+                    total_blocks,
+                    inode_bitmap_blocks,
+                    inode_area_blocks,
+                    data_bitmap_blocks,
+                    data_area_blocks
+                );
+            });
+
+        // The root directory always takes inode 0, and it is the parent of itself.
+        assert_eq!(efs.alloc_inode(), Some(0));
+        efs.modify_disk_inode(0, |disk_inode| disk_inode.initialize(DiskInodeType::Directory));
+        efs.init_dir(0, 0).expect("No space left for the root directory.");
+        block_cache_sync_all();
+
+        Arc::new(Mutex::new(efs))
+    }
+
+    /// Opens the filesystem on a device, or returns `None` if it is not formatted by easy-fs.
+    pub fn open(block_device: Arc<dyn BlockDevice>) -> Option<Arc<Mutex<Self>>> {
+        get_block_cache(0, Arc::clone(&block_device))
+            .lock()
+            .read(0, |super_block: &SuperBlock| {
+                if !super_block.is_valid() {
+                    return None;
+                }
+
+                let inode_total_blocks = super_block.inode_bitmap_blocks + super_block.inode_area_blocks;
+                let efs = Self {
+                    block_device: Arc::clone(&block_device),
+                    inode_bitmap: Bitmap::new(1, super_block.inode_bitmap_blocks as usize),
+                    data_bitmap: Bitmap::new(
+                        (1 + inode_total_blocks) as usize,
+                        super_block.data_bitmap_blocks as usize
+                    ),
+                    inode_area_start_block: 1 + super_block.inode_bitmap_blocks,
+                    data_area_start_block: 1 + inode_total_blocks + super_block.data_bitmap_blocks,
+                    data_area_blocks: super_block.data_area_blocks,
+                    open_inodes: BTreeMap::new()
+                };
+
+                Some(Arc::new(Mutex::new(efs)))
+            })
+    }
+
+    /// Returns the root directory.
+    pub fn root_inode(efs: &Arc<Mutex<Self>>) -> Arc<Inode> {
+        efs.lock().get_inode(0, efs)
+    }
+
+    /// Returns the block of an inode, and its offset in the block.
+    pub fn get_disk_inode_pos(&self, inode_id: u32) -> (u32, usize) {
+        let block_id = self.inode_area_start_block + inode_id / INODES_PER_BLOCK;
+        let block_offset = (inode_id % INODES_PER_BLOCK) as usize * size_of::<DiskInode>();
+
+        (block_id, block_offset)
+    }
+
+    /// Returns the id on the device of a block in the data area.
+    pub fn get_data_block_id(&self, data_block_id: u32) -> u32 {
+        self.data_area_start_block + data_block_id
+    }
+
+    /// Allocates an inode, or returns `None` if there is none left.
+    pub fn alloc_inode(&mut self) -> Option<u32> {
+        self.inode_bitmap
+            .alloc(&self.block_device)
+            .map(|inode_id| inode_id as u32)
+    }
+
+    pub fn dealloc_inode(&mut self, inode_id: u32) {
+        self.inode_bitmap.dealloc(&self.block_device, inode_id as usize);
+    }
+
+    /// Allocates a cleared data block, and returns its id on the device,
+    /// or `None` if the data area is full.
+    pub fn alloc_data(&mut self) -> Option<u32> {
+        let data_block_id = self.data_bitmap.alloc(&self.block_device)? as u32;
+        // The last bitmap block may have more bits than blocks left in the area.
+        if data_block_id >= self.data_area_blocks {
+            self.data_bitmap.dealloc(&self.block_device, data_block_id as usize);
+            return None;
+        }
+
+        let block_id = self.get_data_block_id(data_block_id);
+        get_block_cache(block_id as usize, Arc::clone(&self.block_device))
+            .lock()
+            .modify(0, |data: &mut DataBlock| data.fill(0));
+
+        Some(block_id)
+    }
+
+    /// Frees a data block by its id on the device.
+    pub fn dealloc_data(&mut self, block_id: u32) {
+        self.data_bitmap.dealloc(&self.block_device, (block_id - self.data_area_start_block) as usize);
+    }
+
+    /// Returns the handle of an inode, which is shared while it is in use.
+    pub(crate) fn get_inode(&mut self, inode_id: u32, efs: &Arc<Mutex<Self>>) -> Arc<Inode> {
+        if let Some(inode) = self.open_inodes.get(&inode_id).and_then(Weak::upgrade) {
+            return inode;
+        }
+
+        let inode = Arc::new(Inode::new(inode_id, Arc::clone(efs)));
+        self.open_inodes.insert(inode_id, Arc::downgrade(&inode));
+
+        inode
+    }
+
+    pub(crate) fn is_open(&self, inode_id: u32) -> bool {
+        self.open_inodes
+            .get(&inode_id)
+            .is_some_and(|inode| inode.strong_count() > 0)
+    }
+
+    /// Forgets the handle of an inode once it is dropped, and releases the inode
+    /// if it is unlinked from every directory.
+    pub(crate) fn close_inode(&mut self, inode_id: u32) {
+        if !self.is_open(inode_id) {
+            self.open_inodes.remove(&inode_id);
+        }
+        if self.read_disk_inode(inode_id, |disk_inode| disk_inode.nlink) == 0 {
+            self.release_inode(inode_id);
+        }
+    }
+
+    /// Frees the data blocks of an inode and the inode itself.
+    pub(crate) fn release_inode(&mut self, inode_id: u32) {
+        let (block, offset) = self.disk_inode_block(inode_id);
+        let blocks = block
+            .lock()
+            .modify(offset, |disk_inode: &mut DiskInode| disk_inode.clear_size(&self.block_device));
+        for block_id in blocks {
+            self.dealloc_data(block_id);
+        }
+        self.dealloc_inode(inode_id);
+        block_cache_sync_all();
+    }
+
+    /// Returns the cached block of an inode, and the offset of the inode in it.
+    pub(crate) fn disk_inode_block(&self, inode_id: u32) -> (Arc<Mutex<BlockCache>>, usize) {
+        let (block_id, block_offset) = self.get_disk_inode_pos(inode_id);
+
+        (get_block_cache(block_id as usize, Arc::clone(&self.block_device)), block_offset)
+    }
+
+    pub(crate) fn read_disk_inode<V>(&self, inode_id: u32, f: impl FnOnce(&DiskInode) -> V) -> V {
+        let (block, offset) = self.disk_inode_block(inode_id);
+        block.lock().read(offset, f)
+    }
+
+    pub(crate) fn modify_disk_inode<V>(&self, inode_id: u32, f: impl FnOnce(&mut DiskInode) -> V) -> V {
+        let (block, offset) = self.disk_inode_block(inode_id);
+        block.lock().modify(offset, f)
+    }
+
+    /// Grows `disk_inode` to `new_size` bytes, and allocates no block if the data area is full.
+    pub(crate) fn increase_size(&mut self, disk_inode: &mut DiskInode, new_size: u32) -> FsResult<()> {
+        if new_size <= disk_inode.size {
+            return Ok(());
+        }
+
+        let blocks_needed = disk_inode.blocks_num_needed(new_size);
+        let mut new_blocks = Vec::with_capacity(blocks_needed as usize);
+        for _ in 0..blocks_needed {
+            let Some(block_id) = self.alloc_data() else {
+                for block_id in new_blocks {
+                    self.dealloc_data(block_id);
+                }
+                return Err(FsError::NoSpace);
+            };
+            new_blocks.push(block_id);
+        }
+        disk_inode.increase_size(new_size, new_blocks, &self.block_device);
+
+        Ok(())
+    }
+
+    /// Returns the entry in the slot `slot` of a directory, which may be empty.
+    pub(crate) fn read_entry(&self, dir: &DiskInode, slot: usize) -> DirEntry {
+        let mut entry = DirEntry::empty();
+        dir.read_at(slot * DIRENT_SZ, entry.as_bytes_mut(), &self.block_device);
+
+        entry
+    }
+
+    /// Returns the slot and inode id of the entry named `name` in a directory.
+    pub(crate) fn find_entry(&self, dir: &DiskInode, name: &str) -> Option<(usize, u32)> {
+        (0..dir.size as usize / DIRENT_SZ)
+            .map(|slot| (slot, self.read_entry(dir, slot)))
+            .find(|(_, entry)| !entry.is_empty() && entry.name() == name)
+            .map(|(slot, entry)| (slot, entry.inode_number()))
+    }
+
+    /// Adds an entry to the directory `dir_id`, which takes the first empty slot if there is one,
+    /// and counts a link to the inode.
+    pub(crate) fn add_entry(&mut self, dir_id: u32, name: &str, inode_id: u32) -> FsResult<()> {
+        let (block, offset) = self.disk_inode_block(dir_id);
+        block.lock().modify(offset, |dir: &mut DiskInode| {
+            let count = dir.size as usize / DIRENT_SZ;
+            let slot = (0..count)
+                .find(|&slot| self.read_entry(dir, slot).is_empty())
+                .unwrap_or(count);
+            if slot == count {
+                self.increase_size(dir, ((count + 1) * DIRENT_SZ) as u32)?;
+            }
+            dir.write_at(slot * DIRENT_SZ, DirEntry::new(name, inode_id).as_bytes(), &self.block_device);
+
+            Ok(())
+        })?;
+        self.modify_disk_inode(inode_id, |disk_inode| disk_inode.nlink += 1);
+
+        Ok(())
+    }
+
+    /// Empties the slot `slot` of the directory `dir_id`, and uncounts the link to its inode.
+    pub(crate) fn remove_entry(&mut self, dir_id: u32, slot: usize) {
+        let (block, offset) = self.disk_inode_block(dir_id);
+        let inode_id = block.lock().modify(offset, |dir: &mut DiskInode| {
+            let inode_id = self.read_entry(dir, slot).inode_number();
+            dir.write_at(slot * DIRENT_SZ, DirEntry::empty().as_bytes(), &self.block_device);
+
+            inode_id
+        });
+        self.modify_disk_inode(inode_id, |disk_inode| disk_inode.nlink -= 1);
+    }
+
+    /// Adds `.` and `..` to a new directory, which link to itself and `parent_id`.
+    /// Both fit in the first block, so either both are added or neither is.
+    pub(crate) fn init_dir(&mut self, dir_id: u32, parent_id: u32) -> FsResult<()> {
+        self.add_entry(dir_id, ".", dir_id)?;
+        self.add_entry(dir_id, "..", parent_id)
+    }
+
+    /// Returns whether a directory has no entry besides `.` and `..`.
+    pub(crate) fn is_empty_dir(&self, dir_id: u32) -> bool {
+        self.read_disk_inode(dir_id, |dir| {
+            (0..dir.size as usize / DIRENT_SZ)
+                .map(|slot| self.read_entry(dir, slot))
+                .all(|entry| entry.is_empty() || entry.name() == "." || entry.name() == "..")
+        })
+    }
+}
@@ -0,0 +1,24 @@
+/// Errors of filesystem operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsError {
+    /// No entry has the name.
+    NotFound,
+    /// An entry with the name exists already.
+    AlreadyExists,
+    /// A directory is required, but the inode is not one.
+    NotDir,
+    /// A directory is given where it is not allowed.
+    IsDir,
+    /// The directory to be removed still has entries.
+    NotEmpty,
+    /// The name is longer than [`NAME_LENGTH_LIMIT`](crate::NAME_LENGTH_LIMIT) bytes.
+    NameTooLong,
+    /// No inode or data block is left.
+    NoSpace,
+    /// The name is empty, or refers to `.` or `..` where they cannot be changed.
+    InvalidName,
+    /// The operation is not permitted on the inode, e.g. linking a directory.
+    NotPermitted
+}
+
+pub type FsResult<T> = Result<T, FsError>;
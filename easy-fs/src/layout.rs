@@ -0,0 +1,340 @@
+use alloc::{sync::Arc, vec::Vec};
+use core::slice;
+use super::{
+    BlockDevice, BLOCK_SZ,
+    block_cache::get_block_cache
+};
+
+/// Magic number which marks a device formatted by easy-fs.
+const EFS_MAGIC: u32 = 0x3b800001;
+const INODE_DIRECT_COUNT: usize = 27;
+pub(crate) const INODE_INDIRECT1_COUNT: usize = BLOCK_SZ / 4;
+const INODE_INDIRECT2_COUNT: usize = INODE_INDIRECT1_COUNT * INODE_INDIRECT1_COUNT;
+pub(crate) const DIRECT_BOUND: usize = INODE_DIRECT_COUNT;
+pub(crate) const INDIRECT1_BOUND: usize = DIRECT_BOUND + INODE_INDIRECT1_COUNT;
+const INDIRECT2_BOUND: usize = INDIRECT1_BOUND + INODE_INDIRECT2_COUNT;
+/// Largest size of a file in bytes.
+pub const MAX_FILE_SIZE: usize = INDIRECT2_BOUND * BLOCK_SZ;
+/// Longest name of a directory entry, excluding the trailing NUL byte.
+pub const NAME_LENGTH_LIMIT: usize = 27;
+/// Size of a directory entry in bytes.
+pub const DIRENT_SZ: usize = 32;
+
+/// A block of ids of other blocks.
+type IndirectBlock = [u32; BLOCK_SZ / 4];
+pub type DataBlock = [u8; BLOCK_SZ];
+
+/// The first block of the device, which records the size of each area.
+#[repr(C)]
+pub struct SuperBlock {
+    magic: u32,
+    pub total_blocks: u32,
+    pub inode_bitmap_blocks: u32,
+    pub inode_area_blocks: u32,
+    pub data_bitmap_blocks: u32,
+    pub data_area_blocks: u32
+}
+
+impl SuperBlock {
+    pub fn initialize(
+        &mut self,
+        total_blocks: u32,
+        inode_bitmap_blocks: u32,
+        inode_area_blocks: u32,
+        data_bitmap_blocks: u32,
+        data_area_blocks: u32
+    ) {
+        *self = Self {
+            magic: EFS_MAGIC,
+            total_blocks,
+            inode_bitmap_blocks,
+            inode_area_blocks,
+            data_bitmap_blocks,
+            data_area_blocks
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.magic == EFS_MAGIC
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum DiskInodeType {
+    File,
+    Directory
+}
+
+/// An inode on the disk, whose data blocks are indexed by direct pointers first,
+/// then by a single indirect block, and then by a double indirect block.
+#[repr(C)]
+pub struct DiskInode {
+    pub size: u32,
+    /// Number of directory entries referring to the inode.
+    pub nlink: u32,
+    pub direct: [u32; INODE_DIRECT_COUNT],
+    pub indirect1: u32,
+    pub indirect2: u32,
+    type_: DiskInodeType
+}
+
+impl DiskInode {
+    /// Initializes an empty inode, which owns no block and is not linked yet.
+    pub fn initialize(&mut self, type_: DiskInodeType) {
+        self.size = 0;
+        self.nlink = 0;
+        self.direct.fill(0);
+        self.indirect1 = 0;
+        self.indirect2 = 0;
+        self.type_ = type_;
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.type_ == DiskInodeType::Directory
+    }
+
+    /// Returns the number of data blocks holding `size` bytes.
+    fn data_blocks_of(size: u32) -> u32 {
+        size.div_ceil(BLOCK_SZ as u32)
+    }
+
+    fn data_blocks(&self) -> u32 {
+        Self::data_blocks_of(self.size)
+    }
+
+    /// Returns the number of blocks holding `size` bytes, including the indirect ones.
+    pub fn total_blocks(size: u32) -> u32 {
+        let data_blocks = Self::data_blocks_of(size) as usize;
+        let mut total = data_blocks;
+        if data_blocks > DIRECT_BOUND {
+            total += 1;
+        }
+        if data_blocks > INDIRECT1_BOUND {
+            total += 1 + (data_blocks - INDIRECT1_BOUND).div_ceil(INODE_INDIRECT1_COUNT);
+        }
+
+        total as u32
+    }
+
+    /// Returns the number of blocks to be allocated for growing to `new_size` bytes.
+    pub fn blocks_num_needed(&self, new_size: u32) -> u32 {
+        assert!(new_size >= self.size, "Inode cannot shrink when growing.");
+
+        Self::total_blocks(new_size) - Self::total_blocks(self.size)
+    }
+
+    /// Returns the id of the `inner_id`-th data block on the device.
+    pub fn get_block_id(&self, inner_id: u32, block_device: &Arc<dyn BlockDevice>) -> u32 {
+        let inner_id = inner_id as usize;
+        if inner_id < DIRECT_BOUND {
+            self.direct[inner_id]
+        } else if inner_id < INDIRECT1_BOUND {
+            get_block_cache(self.indirect1 as usize, Arc::clone(block_device))
+                .lock()
+                .read(0, |indirect1: &IndirectBlock| indirect1[inner_id - DIRECT_BOUND])
+        } else {
+            let index = inner_id - INDIRECT1_BOUND;
+            let indirect1 = get_block_cache(self.indirect2 as usize, Arc::clone(block_device))
+                .lock()
+                .read(0, |indirect2: &IndirectBlock| indirect2[index / INODE_INDIRECT1_COUNT]);
+            get_block_cache(indirect1 as usize, Arc::clone(block_device))
+                .lock()
+                .read(0, |indirect1: &IndirectBlock| indirect1[index % INODE_INDIRECT1_COUNT])
+        }
+    }
+
+    /// Grows to `new_size` bytes with `new_blocks`, which are exactly the blocks
+    /// counted by [`Self::blocks_num_needed`].
+    pub fn increase_size(
+        &mut self,
+        new_size: u32,
+        new_blocks: Vec<u32>,
+        block_device: &Arc<dyn BlockDevice>
+    ) {
+        let mut current_blocks = self.data_blocks() as usize;
+        self.size = new_size;
+        let total_blocks = self.data_blocks() as usize;
+        assert!(total_blocks <= INDIRECT2_BOUND, "Inode cannot grow to {} bytes.", new_size);
+        let mut new_blocks = new_blocks.into_iter();
+
+        while current_blocks < total_blocks.min(DIRECT_BOUND) {
+            self.direct[current_blocks] = new_blocks.next().unwrap();
+            current_blocks += 1;
+        }
+        if total_blocks <= DIRECT_BOUND {
+            return;
+        }
+
+        if current_blocks == DIRECT_BOUND {
+            self.indirect1 = new_blocks.next().unwrap();
+        }
+        get_block_cache(self.indirect1 as usize, Arc::clone(block_device))
+            .lock()
+            .modify(0, |indirect1: &mut IndirectBlock| {
+                while current_blocks < total_blocks.min(INDIRECT1_BOUND) {
+                    indirect1[current_blocks - DIRECT_BOUND] = new_blocks.next().unwrap();
+                    current_blocks += 1;
+                }
+            });
+        if total_blocks <= INDIRECT1_BOUND {
+            return;
+        }
+
+        if current_blocks == INDIRECT1_BOUND {
+            self.indirect2 = new_blocks.next().unwrap();
+        }
+        while current_blocks < total_blocks {
+            let index = current_blocks - INDIRECT1_BOUND;
+            // A new single indirect block is taken whenever the previous one is full.
+            let indirect1 = get_block_cache(self.indirect2 as usize, Arc::clone(block_device))
+                .lock()
+                .modify(0, |indirect2: &mut IndirectBlock| {
+                    if index.is_multiple_of(INODE_INDIRECT1_COUNT) {
+                        indirect2[index / INODE_INDIRECT1_COUNT] = new_blocks.next().unwrap();
+                    }
+                    indirect2[index / INODE_INDIRECT1_COUNT]
+                });
+            get_block_cache(indirect1 as usize, Arc::clone(block_device))
+                .lock()
+                .modify(0, |indirect1: &mut IndirectBlock| {
+                    indirect1[index % INODE_INDIRECT1_COUNT] = new_blocks.next().unwrap();
+                });
+            current_blocks += 1;
+        }
+    }
+
+    /// Shrinks to 0 bytes, and returns all blocks owned, including the indirect ones.
+    pub fn clear_size(&mut self, block_device: &Arc<dyn BlockDevice>) -> Vec<u32> {
+        let data_blocks = self.data_blocks() as usize;
+        let mut blocks = Vec::with_capacity(Self::total_blocks(self.size) as usize);
+        self.size = 0;
+
+        blocks.extend_from_slice(&self.direct[..data_blocks.min(DIRECT_BOUND)]);
+        self.direct.fill(0);
+        if data_blocks > DIRECT_BOUND {
+            blocks.push(self.indirect1);
+            get_block_cache(self.indirect1 as usize, Arc::clone(block_device))
+                .lock()
+                .read(0, |indirect1: &IndirectBlock| {
+                    blocks.extend_from_slice(&indirect1[..data_blocks.min(INDIRECT1_BOUND) - DIRECT_BOUND]);
+                });
+            self.indirect1 = 0;
+        }
+
+        if data_blocks > INDIRECT1_BOUND {
+            blocks.push(self.indirect2);
+            let mut remaining = data_blocks - INDIRECT1_BOUND;
+            let indirect1_blocks = get_block_cache(self.indirect2 as usize, Arc::clone(block_device))
+                .lock()
+                .read(0, |indirect2: &IndirectBlock| {
+                    indirect2[..remaining.div_ceil(INODE_INDIRECT1_COUNT)].to_vec()
+                });
+            for indirect1 in indirect1_blocks {
+                blocks.push(indirect1);
+                let count = remaining.min(INODE_INDIRECT1_COUNT);
+                get_block_cache(indirect1 as usize, Arc::clone(block_device))
+                    .lock()
+                    .read(0, |indirect1: &IndirectBlock| blocks.extend_from_slice(&indirect1[..count]));
+                remaining -= count;
+            }
+            self.indirect2 = 0;
+        }
+
+        blocks
+    }
+
+    /// Reads from `offset` into `buf`, and returns the number of bytes read,
+    /// which is less than the length of `buf` at the end of data.
+    pub fn read_at(&self, offset: usize, buf: &mut [u8], block_device: &Arc<dyn BlockDevice>) -> usize {
+        let mut start = offset;
+        let end = (offset + buf.len()).min(self.size as usize);
+        let mut read_size = 0;
+        while start < end {
+            let block_end = ((start / BLOCK_SZ + 1) * BLOCK_SZ).min(end);
+            let len = block_end - start;
+            let block_offset = start % BLOCK_SZ;
+            let block_id = self.get_block_id((start / BLOCK_SZ) as u32, block_device);
+            get_block_cache(block_id as usize, Arc::clone(block_device))
+                .lock()
+                .read(0, |data: &DataBlock| {
+                    buf[read_size..read_size + len].copy_from_slice(&data[block_offset..block_offset + len]);
+                });
+            read_size += len;
+            start = block_end;
+        }
+
+        read_size
+    }
+
+    /// Writes `buf` from `offset`, and returns the number of bytes written.
+    /// The size must be increased in advance, as nothing is written beyond it.
+    pub fn write_at(&mut self, offset: usize, buf: &[u8], block_device: &Arc<dyn BlockDevice>) -> usize {
+        let mut start = offset;
+        let end = (offset + buf.len()).min(self.size as usize);
+        let mut write_size = 0;
+        while start < end {
+            let block_end = ((start / BLOCK_SZ + 1) * BLOCK_SZ).min(end);
+            let len = block_end - start;
+            let block_offset = start % BLOCK_SZ;
+            let block_id = self.get_block_id((start / BLOCK_SZ) as u32, block_device);
+            get_block_cache(block_id as usize, Arc::clone(block_device))
+                .lock()
+                .modify(0, |data: &mut DataBlock| {
+                    data[block_offset..block_offset + len].copy_from_slice(&buf[write_size..write_size + len]);
+                });
+            write_size += len;
+            start = block_end;
+        }
+
+        write_size
+    }
+}
+
+/// An entry of a directory, which maps a name to an inode.
+/// Removed entries are left empty, and their slots are reused later.
+#[repr(C)]
+pub struct DirEntry {
+    name: [u8; NAME_LENGTH_LIMIT + 1],
+    inode_number: u32
+}
+
+impl DirEntry {
+    pub fn empty() -> Self {
+        Self {
+            name: [0; NAME_LENGTH_LIMIT + 1],
+            inode_number: 0
+        }
+    }
+
+    /// Creates an entry, where `name` is at most [`NAME_LENGTH_LIMIT`] bytes long.
+    pub fn new(name: &str, inode_number: u32) -> Self {
+        assert!(name.len() <= NAME_LENGTH_LIMIT, "Name {} is too long.", name);
+        let mut entry = Self::empty();
+        entry.name[..name.len()].copy_from_slice(name.as_bytes());
+        entry.inode_number = inode_number;
+
+        entry
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self as *const Self as *const u8, DIRENT_SZ) }
+    }
+
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self as *mut Self as *mut u8, DIRENT_SZ) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.name[0] == 0
+    }
+
+    pub fn name(&self) -> &str {
+        let len = self.name.iter().position(|&ch| ch == 0).unwrap_or(NAME_LENGTH_LIMIT);
+        str::from_utf8(&self.name[..len]).unwrap_or_default()
+    }
+
+    pub fn inode_number(&self) -> u32 {
+        self.inode_number
+    }
+}
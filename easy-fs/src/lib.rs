@@ -0,0 +1,30 @@
+//! A simple block-based filesystem, which works on any [`BlockDevice`].
+//!
+//! The disk is laid out as a super block, an inode bitmap, the inode area,
+//! a data bitmap and the data area in order.
+
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+// Include section.
+mod bitmap;
+mod block_cache;
+mod block_dev;
+mod efs;
+mod error;
+mod layout;
+mod vfs;
+#[cfg(test)]
+mod tests;
+
+// Export section.
+pub use block_cache::block_cache_sync_all;
+pub use block_dev::BlockDevice;
+pub use efs::EasyFileSystem;
+pub use error::{FsError, FsResult};
+pub use layout::NAME_LENGTH_LIMIT;
+pub use vfs::{DirEntryInfo, Inode};
+
+/// Size of a block in bytes.
+pub const BLOCK_SZ: usize = 512;
@@ -0,0 +1,322 @@
+use std::{
+    env, fs, iter, process,
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::Path,
+    sync::{Arc, Mutex as StdMutex, MutexGuard}
+};
+use spin::Mutex;
+use super::{
+    BlockDevice, EasyFileSystem, FsError, Inode, BLOCK_SZ,
+    block_cache::get_block_cache,
+    layout::{DiskInode, SuperBlock, DIRECT_BOUND, DIRENT_SZ, INDIRECT1_BOUND, INODE_INDIRECT1_COUNT}
+};
+
+/// Blocks of a test device, which leave about 1000 blocks for data after 1024 blocks of inodes.
+const TOTAL_BLOCKS: u32 = 2048;
+const INODE_BITMAP_BLOCKS: u32 = 1;
+
+/// The block cache is shared by all tests, and it runs out if too many of them hold blocks at once.
+static SERIAL: StdMutex<()> = StdMutex::new(());
+
+fn serial() -> MutexGuard<'static, ()> {
+    SERIAL.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// A device kept in memory.
+struct MemDevice(StdMutex<Vec<[u8; BLOCK_SZ]>>);
+
+impl BlockDevice for MemDevice {
+    fn read_block(&self, block_id: usize, buf: &mut [u8]) {
+        buf.copy_from_slice(&self.0.lock().unwrap()[block_id]);
+    }
+
+    fn write_block(&self, block_id: usize, buf: &[u8]) {
+        self.0.lock().unwrap()[block_id].copy_from_slice(buf);
+    }
+}
+
+fn mem_device(total_blocks: u32) -> Arc<dyn BlockDevice> {
+    Arc::new(MemDevice(StdMutex::new(vec![[0; BLOCK_SZ]; total_blocks as usize])))
+}
+
+/// A device backed by an image file, as in `easy-fs-fuse`.
+struct FileDevice(StdMutex<File>);
+
+impl BlockDevice for FileDevice {
+    fn read_block(&self, block_id: usize, buf: &mut [u8]) {
+        let mut file = self.0.lock().unwrap();
+        file.seek(SeekFrom::Start((block_id * BLOCK_SZ) as u64)).unwrap();
+        file.read_exact(buf).unwrap();
+    }
+
+    fn write_block(&self, block_id: usize, buf: &[u8]) {
+        let mut file = self.0.lock().unwrap();
+        file.seek(SeekFrom::Start((block_id * BLOCK_SZ) as u64)).unwrap();
+        file.write_all(buf).unwrap();
+    }
+}
+
+fn file_device(path: &Path) -> Arc<dyn BlockDevice> {
+    let file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path).unwrap();
+    file.set_len(TOTAL_BLOCKS as u64 * BLOCK_SZ as u64).unwrap();
+
+    Arc::new(FileDevice(StdMutex::new(file)))
+}
+
+fn create_fs() -> (Arc<dyn BlockDevice>, Arc<Mutex<EasyFileSystem>>) {
+    let device = mem_device(TOTAL_BLOCKS);
+    let efs = EasyFileSystem::create(Arc::clone(&device), TOTAL_BLOCKS, INODE_BITMAP_BLOCKS);
+
+    (device, efs)
+}
+
+/// Counts the free data blocks by allocating all of them, and then frees them again.
+fn free_data_blocks(efs: &Arc<Mutex<EasyFileSystem>>) -> usize {
+    let mut fs = efs.lock();
+    let blocks: Vec<u32> = iter::from_fn(|| fs.alloc_data()).collect();
+    for &block_id in blocks.iter() {
+        fs.dealloc_data(block_id);
+    }
+
+    blocks.len()
+}
+
+fn pattern(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
+
+fn read_all(inode: &Inode) -> Vec<u8> {
+    let mut buf = vec![0; inode.size()];
+    assert_eq!(inode.read_at(0, &mut buf), buf.len());
+
+    buf
+}
+
+#[test]
+fn create_and_open_round_trip() {
+    let _guard = serial();
+    let (device, efs) = create_fs();
+    let root = EasyFileSystem::root_inode(&efs);
+    let file = root.create("hello").unwrap();
+    assert_eq!(file.write_at(0, b"Hello, world!"), 13);
+    root.mkdir("dir").unwrap();
+    drop((file, root, efs));
+
+    let efs = EasyFileSystem::open(Arc::clone(&device)).expect("Failed to open the filesystem.");
+    let root = EasyFileSystem::root_inode(&efs);
+    assert_eq!(root.ls(), [".", "..", "hello", "dir"]);
+    let file = root.find("hello").unwrap();
+    assert!(!file.is_dir());
+    assert_eq!(read_all(&file), b"Hello, world!");
+    assert!(root.find("dir").unwrap().is_dir());
+
+    assert!(EasyFileSystem::open(mem_device(TOTAL_BLOCKS)).is_none());
+}
+
+#[test]
+fn devices_do_not_share_blocks() {
+    let _guard = serial();
+    let (_, efs1) = create_fs();
+    let (_, efs2) = create_fs();
+    let root1 = EasyFileSystem::root_inode(&efs1);
+    let root2 = EasyFileSystem::root_inode(&efs2);
+
+    root1.create("only_in_1").unwrap();
+    assert!(root2.find("only_in_1").is_none());
+    assert_eq!(root2.ls(), [".", ".."]);
+}
+
+#[test]
+fn grow_and_shrink_through_indirect_blocks() {
+    let _guard = serial();
+    let (_, efs) = create_fs();
+    let root = EasyFileSystem::root_inode(&efs);
+    let file = root.create("big").unwrap();
+    let free = free_data_blocks(&efs);
+
+    // Each size crosses into the next level of indexing, up to a second block under `indirect2`.
+    let sizes = [
+        10,
+        DIRECT_BOUND,
+        DIRECT_BOUND + 1,
+        INDIRECT1_BOUND,
+        INDIRECT1_BOUND + 1,
+        INDIRECT1_BOUND + INODE_INDIRECT1_COUNT + 1
+    ];
+    let data = pattern(sizes[sizes.len() - 1] * BLOCK_SZ);
+    for round in 0..2 {
+        let mut size = 0;
+        for blocks in sizes {
+            let new_size = blocks * BLOCK_SZ;
+            assert_eq!(file.write_at(size, &data[size..new_size]), new_size - size);
+            size = new_size;
+            assert_eq!(file.size(), size);
+            assert_eq!(read_all(&file), &data[..size], "Round {} at {} blocks.", round, blocks);
+            let used = DiskInode::total_blocks(size as u32) as usize;
+            assert_eq!(free_data_blocks(&efs), free - used, "Round {} at {} blocks.", round, blocks);
+        }
+
+        file.clear();
+        assert_eq!(file.size(), 0);
+        assert_eq!(free_data_blocks(&efs), free);
+    }
+}
+
+#[test]
+fn alloc_data_stops_at_data_area_blocks() {
+    let _guard = serial();
+    let (device, efs) = create_fs();
+    let data_area_blocks = get_block_cache(0, Arc::clone(&device))
+        .lock()
+        .read(0, |super_block: &SuperBlock| super_block.data_area_blocks);
+    // The bitmap has bits for more blocks than the area holds.
+    assert!(!(data_area_blocks as usize).is_multiple_of(BLOCK_SZ * 8));
+
+    let mut fs = efs.lock();
+    let blocks: Vec<u32> = iter::from_fn(|| fs.alloc_data()).collect();
+    // The root directory takes one block already.
+    assert_eq!(blocks.len(), data_area_blocks as usize - 1);
+    assert_eq!(blocks.iter().max(), Some(&fs.get_data_block_id(data_area_blocks - 1)));
+    assert_eq!(fs.alloc_data(), None);
+
+    // Running out of space fails the growth without taking any block.
+    fs.dealloc_data(blocks[0]);
+    drop(fs);
+    let root = EasyFileSystem::root_inode(&efs);
+    let file = root.create("file").unwrap();
+    assert_eq!(file.write_at(0, &pattern(2 * BLOCK_SZ)), 0);
+    assert_eq!(file.size(), 0);
+    assert_eq!(file.write_at(0, &pattern(BLOCK_SZ)), BLOCK_SZ);
+    assert!(root.create("more").is_ok());
+    assert_eq!(root.mkdir("dir").err(), Some(FsError::NoSpace));
+}
+
+#[test]
+fn directory_slots_are_reused() {
+    let _guard = serial();
+    let (_, efs) = create_fs();
+    let root = EasyFileSystem::root_inode(&efs);
+    for name in ["a", "b", "c"] {
+        root.create(name).unwrap();
+    }
+    let dir_size = root.size();
+    assert_eq!(dir_size, 5 * DIRENT_SZ);
+
+    root.unlink("b").unwrap();
+    assert!(root.find("b").is_none());
+    root.mkdir("d").unwrap();
+    assert_eq!(root.size(), dir_size);
+    assert_eq!(root.ls(), [".", "..", "a", "d", "c"]);
+
+    root.rmdir("d").unwrap();
+    root.create("e").unwrap();
+    root.create("f").unwrap();
+    assert_eq!(root.size(), dir_size + DIRENT_SZ);
+    assert_eq!(root.ls(), [".", "..", "a", "e", "c", "f"]);
+}
+
+#[test]
+fn file_device_round_trip() {
+    let _guard = serial();
+    let path = env::temp_dir().join(format!("easy-fs-test-{}.img", process::id()));
+    let efs = EasyFileSystem::create(file_device(&path), TOTAL_BLOCKS, INODE_BITMAP_BLOCKS);
+    let root = EasyFileSystem::root_inode(&efs);
+    let data = pattern(DIRECT_BOUND * BLOCK_SZ + 100);
+    let file = root.mkdir("dir").unwrap().create("file").unwrap();
+    assert_eq!(file.write_at(0, &data), data.len());
+    drop((file, root, efs));
+
+    // The image is reopened through a new device, so every block is read back from the file.
+    let efs = EasyFileSystem::open(file_device(&path)).expect("Failed to open the image.");
+    let root = EasyFileSystem::root_inode(&efs);
+    let file = root.find("dir").unwrap().find("file").unwrap();
+    assert_eq!(read_all(&file), data);
+    drop((file, root, efs));
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn inode_is_freed_with_last_link() {
+    let _guard = serial();
+    let (_, efs) = create_fs();
+    let root = EasyFileSystem::root_inode(&efs);
+    let dir = root.mkdir("dir").unwrap();
+    let free = free_data_blocks(&efs);
+
+    let file = root.create("a").unwrap();
+    let inode_id = file.inode_id();
+    assert_eq!(file.write_at(0, &pattern(BLOCK_SZ)), BLOCK_SZ);
+    root.link("b", &file).unwrap();
+    dir.link("c", &file).unwrap();
+    assert_eq!(file.nlink(), 3);
+    assert_eq!(root.link("d", &dir).err(), Some(FsError::NotPermitted));
+    drop(file);
+
+    root.unlink("a").unwrap();
+    root.unlink("b").unwrap();
+    let file = dir.find("c").unwrap();
+    assert_eq!(file.nlink(), 1);
+    assert_eq!(file.inode_id(), inode_id);
+    assert_eq!(read_all(&file), pattern(BLOCK_SZ));
+
+    // An open file outlives its last link, and is freed once it is closed.
+    dir.unlink("c").unwrap();
+    assert_eq!(file.nlink(), 0);
+    assert_eq!(read_all(&file), pattern(BLOCK_SZ));
+    assert!(free_data_blocks(&efs) < free);
+    drop(file);
+    assert_eq!(free_data_blocks(&efs), free);
+    assert_eq!(root.create("e").unwrap().inode_id(), inode_id);
+}
+
+#[test]
+fn next_entry_skips_freed_slots() {
+    let _guard = serial();
+    let (_, efs) = create_fs();
+    let root = EasyFileSystem::root_inode(&efs);
+    for name in ["a", "b", "c", "d"] {
+        root.create(name).unwrap();
+    }
+    root.mkdir("e").unwrap();
+    root.unlink("b").unwrap();
+    root.unlink("c").unwrap();
+
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    while let Some((entry, next_offset)) = root.next_entry(offset) {
+        assert!(next_offset > offset);
+        entries.push((entry.name, entry.is_dir, next_offset));
+        offset = next_offset;
+    }
+    assert_eq!(entries, [
+        (String::from("."), true, DIRENT_SZ),
+        (String::from(".."), true, 2 * DIRENT_SZ),
+        (String::from("a"), false, 3 * DIRENT_SZ),
+        (String::from("d"), false, 6 * DIRENT_SZ),
+        (String::from("e"), true, 7 * DIRENT_SZ)
+    ]);
+    // An offset in the middle of a slot resumes from the next one.
+    assert_eq!(root.next_entry(DIRENT_SZ + 1).unwrap().0.name, "a");
+    assert!(root.find("a").unwrap().next_entry(0).is_none());
+}
+
+#[test]
+fn rmdir_keeps_non_empty_directory() {
+    let _guard = serial();
+    let (_, efs) = create_fs();
+    let root = EasyFileSystem::root_inode(&efs);
+    let dir = root.mkdir("dir").unwrap();
+    dir.create("file").unwrap();
+    let root_nlink = root.nlink();
+
+    assert_eq!(root.rmdir("dir").err(), Some(FsError::NotEmpty));
+    assert_eq!(root.nlink(), root_nlink);
+    assert!(root.find("dir").is_some());
+    assert_eq!(dir.ls(), [".", "..", "file"]);
+
+    dir.unlink("file").unwrap();
+    root.rmdir("dir").unwrap();
+    assert!(root.find("dir").is_none());
+    assert_eq!(root.nlink(), root_nlink - 1);
+}
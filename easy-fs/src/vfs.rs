@@ -0,0 +1,264 @@
+use alloc::{string::String, sync::Arc, vec::Vec};
+use spin::Mutex;
+use super::{
+    EasyFileSystem, FsError, FsResult,
+    block_cache::block_cache_sync_all,
+    layout::{DiskInode, DiskInodeType, DIRENT_SZ, MAX_FILE_SIZE, NAME_LENGTH_LIMIT}
+};
+
+/// A handle to an inode, through which files and directories are accessed.
+/// There is at most one handle to an inode at a time, and an unlinked inode
+/// is released when its handle is dropped.
+pub struct Inode {
+    inode_id: u32,
+    fs: Arc<Mutex<EasyFileSystem>>
+}
+
+/// An entry of a directory, as returned by [`Inode::next_entry`].
+#[derive(Debug, Clone)]
+pub struct DirEntryInfo {
+    pub name: String,
+    pub inode_id: u32,
+    pub is_dir: bool
+}
+
+/// Checks that `name` can be given to a new entry.
+fn check_name(name: &str) -> FsResult<()> {
+    if name.is_empty() || name.contains('/') {
+        Err(FsError::InvalidName)
+    } else if name.len() > NAME_LENGTH_LIMIT {
+        Err(FsError::NameTooLong)
+    } else {
+        Ok(())
+    }
+}
+
+impl Inode {
+    /// Creates a handle to an inode, which should only be done by [`EasyFileSystem`]
+    /// so that the handle is shared.
+    pub(crate) fn new(inode_id: u32, fs: Arc<Mutex<EasyFileSystem>>) -> Self {
+        Self { inode_id, fs }
+    }
+
+    /// Returns the id of the inode, which is unique in the filesystem.
+    pub fn inode_id(&self) -> u32 {
+        self.inode_id
+    }
+
+    fn read_disk_inode<V>(&self, f: impl FnOnce(&DiskInode) -> V) -> V {
+        self.fs.lock().read_disk_inode(self.inode_id, f)
+    }
+
+    /// Returns the size in bytes.
+    pub fn size(&self) -> usize {
+        self.read_disk_inode(|disk_inode| disk_inode.size as usize)
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.read_disk_inode(|disk_inode| disk_inode.is_dir())
+    }
+
+    /// Returns the number of entries linking to the inode, counting `.` and `..` of directories.
+    pub fn nlink(&self) -> u32 {
+        self.read_disk_inode(|disk_inode| disk_inode.nlink)
+    }
+
+    /// Finds the entry named `name` in this directory.
+    pub fn find(&self, name: &str) -> Option<Arc<Self>> {
+        let mut fs = self.fs.lock();
+        let (_, inode_id) = fs.read_disk_inode(self.inode_id, |dir| {
+            dir.is_dir().then(|| fs.find_entry(dir, name)).flatten()
+        })?;
+
+        Some(fs.get_inode(inode_id, &self.fs))
+    }
+
+    /// Checks that this is a live directory without an entry named `name`.
+    fn check_absent(&self, fs: &EasyFileSystem, name: &str) -> FsResult<()> {
+        fs.read_disk_inode(self.inode_id, |dir| {
+            if !dir.is_dir() {
+                Err(FsError::NotDir)
+            } else if dir.nlink == 0 {
+                // The directory is removed, though still open.
+                Err(FsError::NotFound)
+            } else if fs.find_entry(dir, name).is_some() {
+                Err(FsError::AlreadyExists)
+            } else {
+                Ok(())
+            }
+        })
+    }
+
+    /// Creates a regular file named `name` in this directory.
+    pub fn create(&self, name: &str) -> FsResult<Arc<Self>> {
+        self.create_inode(name, DiskInodeType::File)
+    }
+
+    /// Creates a directory named `name` in this directory, which has `.` and `..` in it.
+    pub fn mkdir(&self, name: &str) -> FsResult<Arc<Self>> {
+        self.create_inode(name, DiskInodeType::Directory)
+    }
+
+    fn create_inode(&self, name: &str, type_: DiskInodeType) -> FsResult<Arc<Self>> {
+        check_name(name)?;
+        let mut fs = self.fs.lock();
+        self.check_absent(&fs, name)?;
+
+        let inode_id = fs.alloc_inode().ok_or(FsError::NoSpace)?;
+        fs.modify_disk_inode(inode_id, |disk_inode| disk_inode.initialize(type_));
+        if type_ == DiskInodeType::Directory
+            && let Err(err) = fs.init_dir(inode_id, self.inode_id)
+        {
+            fs.release_inode(inode_id);
+            return Err(err);
+        }
+        if let Err(err) = fs.add_entry(self.inode_id, name, inode_id) {
+            // Drops the link from `..` of the new directory as well.
+            if type_ == DiskInodeType::Directory {
+                fs.modify_disk_inode(self.inode_id, |dir| dir.nlink -= 1);
+            }
+            fs.release_inode(inode_id);
+            return Err(err);
+        }
+        block_cache_sync_all();
+
+        Ok(fs.get_inode(inode_id, &self.fs))
+    }
+
+    /// Adds an entry named `name` in this directory, which links to `target`.
+    /// Directories cannot be linked, so that there is no cycle.
+    pub fn link(&self, name: &str, target: &Inode) -> FsResult<()> {
+        check_name(name)?;
+        let mut fs = self.fs.lock();
+        if fs.read_disk_inode(target.inode_id, |disk_inode| disk_inode.is_dir()) {
+            return Err(FsError::NotPermitted);
+        }
+        self.check_absent(&fs, name)?;
+        fs.add_entry(self.inode_id, name, target.inode_id)?;
+        block_cache_sync_all();
+
+        Ok(())
+    }
+
+    /// Removes the entry named `name` in this directory, which must not be a directory.
+    pub fn unlink(&self, name: &str) -> FsResult<()> {
+        self.remove(name, false)
+    }
+
+    /// Removes the empty directory named `name` in this directory.
+    pub fn rmdir(&self, name: &str) -> FsResult<()> {
+        self.remove(name, true)
+    }
+
+    fn remove(&self, name: &str, remove_dir: bool) -> FsResult<()> {
+        if name == "." || name == ".." {
+            return Err(FsError::InvalidName);
+        }
+
+        let mut fs = self.fs.lock();
+        let (slot, inode_id) = fs.read_disk_inode(self.inode_id, |dir| {
+            if !dir.is_dir() {
+                return Err(FsError::NotDir);
+            }
+            fs.find_entry(dir, name).ok_or(FsError::NotFound)
+        })?;
+        let is_dir = fs.read_disk_inode(inode_id, |disk_inode| disk_inode.is_dir());
+        match (is_dir, remove_dir) {
+            (true, false) => return Err(FsError::IsDir),
+            (false, true) => return Err(FsError::NotDir),
+            (true, true) if !fs.is_empty_dir(inode_id) => return Err(FsError::NotEmpty),
+            _ => ()
+        }
+
+        fs.remove_entry(self.inode_id, slot);
+        if is_dir {
+            // `.` and `..` of the directory go away along with it.
+            fs.modify_disk_inode(inode_id, |dir| dir.nlink -= 1);
+            fs.modify_disk_inode(self.inode_id, |dir| dir.nlink -= 1);
+        }
+        // An inode still in use is released once its handle is dropped.
+        if fs.read_disk_inode(inode_id, |disk_inode| disk_inode.nlink) == 0 && !fs.is_open(inode_id) {
+            fs.release_inode(inode_id);
+        }
+        block_cache_sync_all();
+
+        Ok(())
+    }
+
+    /// Returns the names of all entries in this directory.
+    pub fn ls(&self) -> Vec<String> {
+        let fs = self.fs.lock();
+        fs.read_disk_inode(self.inode_id, |dir| {
+            (0..dir.size as usize / DIRENT_SZ)
+                .map(|slot| fs.read_entry(dir, slot))
+                .filter(|entry| !entry.is_empty())
+                .map(|entry| String::from(entry.name()))
+                .collect()
+        })
+    }
+
+    /// Returns the first entry of this directory at or after byte `offset`,
+    /// and the offset right after it, or `None` if there are no more entries.
+    pub fn next_entry(&self, offset: usize) -> Option<(DirEntryInfo, usize)> {
+        let fs = self.fs.lock();
+        let (slot, entry) = fs.read_disk_inode(self.inode_id, |dir| {
+            if !dir.is_dir() {
+                return None;
+            }
+            (offset.div_ceil(DIRENT_SZ)..dir.size as usize / DIRENT_SZ)
+                .map(|slot| (slot, fs.read_entry(dir, slot)))
+                .find(|(_, entry)| !entry.is_empty())
+        })?;
+        let info = DirEntryInfo {
+            name: String::from(entry.name()),
+            inode_id: entry.inode_number(),
+            is_dir: fs.read_disk_inode(entry.inode_number(), |disk_inode| disk_inode.is_dir())
+        };
+
+        Some((info, (slot + 1) * DIRENT_SZ))
+    }
+
+    /// Reads from `offset` into `buf`, and returns the number of bytes read.
+    pub fn read_at(&self, offset: usize, buf: &mut [u8]) -> usize {
+        let fs = self.fs.lock();
+        fs.read_disk_inode(self.inode_id, |disk_inode| disk_inode.read_at(offset, buf, &fs.block_device))
+    }
+
+    /// Writes `buf` from `offset`, and grows the file if needed. Returns the number of bytes
+    /// written, which is 0 if there is no space left to grow, or the file would be too large.
+    pub fn write_at(&self, offset: usize, buf: &[u8]) -> usize {
+        let Some(end) = offset.checked_add(buf.len()).filter(|&end| end <= MAX_FILE_SIZE) else {
+            return 0;
+        };
+
+        let mut fs = self.fs.lock();
+        let (block, block_offset) = fs.disk_inode_block(self.inode_id);
+        let size = block.lock().modify(block_offset, |disk_inode: &mut DiskInode| {
+            fs.increase_size(disk_inode, end as u32).ok()?;
+
+            Some(disk_inode.write_at(offset, buf, &fs.block_device))
+        });
+        block_cache_sync_all();
+
+        size.unwrap_or(0)
+    }
+
+    /// Truncates the file to 0 bytes, and frees all its blocks.
+    pub fn clear(&self) {
+        let mut fs = self.fs.lock();
+        let (block, block_offset) = fs.disk_inode_block(self.inode_id);
+        let blocks = block
+            .lock()
+            .modify(block_offset, |disk_inode: &mut DiskInode| disk_inode.clear_size(&fs.block_device));
+        for block_id in blocks {
+            fs.dealloc_data(block_id);
+        }
+        block_cache_sync_all();
+    }
+}
+
+impl Drop for Inode {
+    fn drop(&mut self) {
+        self.fs.lock().close_inode(self.inode_id);
+    }
+}
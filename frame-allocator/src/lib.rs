@@ -0,0 +1,166 @@
+//! Allocators of physical frames, which work on plain page numbers so that
+//! they can be tested on the host.
+
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+pub trait FrameAllocator {
+    fn alloc(&mut self) -> Option<usize>;
+    fn dealloc(&mut self, ppn: usize);
+}
+
+/// Hands out frames in `[current, end)` linearly, and prefers the recycled ones,
+/// which are kept in a stack.
+pub struct StackFrameAllocator {
+    start: usize,
+    current: usize,
+    end: usize,
+    recycled: Vec<usize>
+}
+
+impl StackFrameAllocator {
+    pub const fn new() -> Self {
+        Self {
+            start: 0,
+            current: 0,
+            end: 0,
+            recycled: Vec::new()
+        }
+    }
+
+    /// Manages the frames in `[start, end)`, forgetting about any previous ones.
+    pub fn init(&mut self, start: usize, end: usize) {
+        self.start = start;
+        self.current = start;
+        self.end = end;
+        self.recycled.clear();
+    }
+
+    /// Hands out `count` frames with consecutive numbers, and returns the first one.
+    /// Recycled frames are skipped, as they are scattered in general.
+    pub fn alloc_contiguous(&mut self, count: usize) -> Option<usize> {
+        if self.end - self.current < count {
+            return None;
+        }
+
+        self.current += count;
+
+        Some(self.current - count)
+    }
+}
+
+impl Default for StackFrameAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameAllocator for StackFrameAllocator {
+    fn alloc(&mut self) -> Option<usize> {
+        if let Some(ppn) = self.recycled.pop() {
+            Some(ppn)
+        } else if self.current == self.end {
+            None
+        } else {
+            self.current += 1;
+            Some(self.current - 1)
+        }
+    }
+
+    fn dealloc(&mut self, ppn: usize) {
+        if ppn < self.start || ppn >= self.current || self.recycled.contains(&ppn) {
+            panic!("Frame ppn = {:#x} has not been allocated.", ppn);
+        }
+
+        self.recycled.push(ppn);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allocator(start: usize, end: usize) -> StackFrameAllocator {
+        let mut allocator = StackFrameAllocator::new();
+        allocator.init(start, end);
+
+        allocator
+    }
+
+    #[test]
+    fn alloc_until_exhaustion() {
+        let mut allocator = allocator(0x80, 0x84);
+        let frames: Vec<_> = (0..4).map(|_| allocator.alloc()).collect();
+        assert_eq!(frames, [Some(0x80), Some(0x81), Some(0x82), Some(0x83)]);
+        assert_eq!(allocator.alloc(), None);
+
+        allocator.dealloc(0x81);
+        assert_eq!(allocator.alloc(), Some(0x81));
+        assert_eq!(allocator.alloc(), None);
+    }
+
+    #[test]
+    fn recycles_in_lifo_order() {
+        let mut allocator = allocator(0x80, 0x90);
+        for _ in 0..3 {
+            allocator.alloc();
+        }
+        allocator.dealloc(0x80);
+        allocator.dealloc(0x82);
+        allocator.dealloc(0x81);
+
+        assert_eq!(allocator.alloc(), Some(0x81));
+        assert_eq!(allocator.alloc(), Some(0x82));
+        assert_eq!(allocator.alloc(), Some(0x80));
+        assert_eq!(allocator.alloc(), Some(0x83));
+    }
+
+    #[test]
+    #[should_panic(expected = "has not been allocated")]
+    fn double_free_panics() {
+        let mut allocator = allocator(0x80, 0x90);
+        let ppn = allocator.alloc().unwrap();
+        allocator.dealloc(ppn);
+        allocator.dealloc(ppn);
+    }
+
+    #[test]
+    #[should_panic(expected = "has not been allocated")]
+    fn free_beyond_current_panics() {
+        let mut allocator = allocator(0x80, 0x90);
+        allocator.alloc();
+        allocator.dealloc(0x81);
+    }
+
+    #[test]
+    #[should_panic(expected = "has not been allocated")]
+    fn free_below_start_panics() {
+        let mut allocator = allocator(0x80, 0x90);
+        allocator.alloc();
+        allocator.dealloc(0x7f);
+    }
+
+    #[test]
+    fn alloc_contiguous_skips_recycled() {
+        let mut allocator = allocator(0x80, 0x88);
+        allocator.alloc();
+        allocator.dealloc(0x80);
+
+        assert_eq!(allocator.alloc_contiguous(4), Some(0x81));
+        assert_eq!(allocator.alloc_contiguous(4), None);
+        assert_eq!(allocator.alloc_contiguous(3), Some(0x85));
+        assert_eq!(allocator.alloc_contiguous(1), None);
+        assert_eq!(allocator.alloc_contiguous(0), Some(0x88));
+        // The recycled frame is still handed out one at a time.
+        assert_eq!(allocator.alloc(), Some(0x80));
+        assert_eq!(allocator.alloc(), None);
+
+        for ppn in 0x81..0x85 {
+            allocator.dealloc(ppn);
+        }
+        assert_eq!(allocator.alloc(), Some(0x84));
+    }
+}
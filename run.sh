@@ -29,6 +29,14 @@ osr_run() {
 
     release_dir="target/riscv64gc-unknown-none-elf/release/"
 
+    # Packs the apps into a filesystem image.
+    cd ./easy-fs-fuse
+    cargo run --release -- create \
+        --source ../user/src/bin \
+        --target ../user/target/riscv64gc-unknown-none-elf/release/ \
+        --image ../target/fs.img
+    cd ../
+
     # Clears the metadata.
     rust-objcopy --strip-all ${release_dir}os -O binary ${release_dir}os.bin
     rust-objdump --arch-name=riscv64 -x ${release_dir}os > disasm.asm
@@ -41,7 +49,9 @@ osr_run() {
         -machine virt \
         -nographic \
         -bios ../bootloader/rustsbi-qemu.bin \
-        -device loader,file=${release_dir}os.bin,addr=0x80200000
+        -device loader,file=${release_dir}os.bin,addr=0x80200000 \
+        -drive file=target/fs.img,if=none,format=raw,id=x0 \
+        -device virtio-blk-device,drive=x0,bus=virtio-mmio-bus.0
 }
 
 osr_clean() {
@@ -49,6 +59,9 @@ osr_clean() {
     cd user
     cargo clean
     cd ../
+    cd easy-fs-fuse
+    cargo clean
+    cd ../
 }
 
 osr_check() {
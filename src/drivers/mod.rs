@@ -0,0 +1,14 @@
+use alloc::sync::Arc;
+use easy_fs::BlockDevice;
+use lazy_static::lazy_static;
+
+// Include section.
+mod virtio_blk;
+
+// Export section.
+pub use virtio_blk::VirtIOBlock;
+
+lazy_static! {
+    /// The block device which holds the filesystem image.
+    pub static ref BLOCK_DEVICE: Arc<dyn BlockDevice> = Arc::new(VirtIOBlock::new());
+}
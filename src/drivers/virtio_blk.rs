@@ -0,0 +1,85 @@
+use alloc::vec::Vec;
+use easy_fs::BlockDevice;
+use lazy_static::lazy_static;
+use virtio_drivers::{Hal, VirtIOBlk, VirtIOHeader};
+use crate::{
+    mm::{frame_alloc_contiguous, FrameTracker, KERNEL_SPACE, PhysAddr, PhysPageNum, VirtAddr},
+    sync::UPCell
+};
+
+/// Registers of the first VirtIO device on qemu `virt` machine.
+const VIRTIO0: usize = 0x10001000;
+
+lazy_static! {
+    /// Frames of the DMA areas, which are kept until the driver frees them.
+    static ref QUEUE_FRAMES: UPCell<Vec<FrameTracker>> = unsafe { UPCell::new(Vec::new()) };
+}
+
+/// The VirtIO block device attached by qemu with `-device virtio-blk-device`.
+pub struct VirtIOBlock(UPCell<VirtIOBlk<'static, VirtIOHal>>);
+
+impl VirtIOBlock {
+    pub fn new() -> Self {
+        let header = unsafe { &mut *(VIRTIO0 as *mut VirtIOHeader) };
+        let blk = VirtIOBlk::<VirtIOHal>::new(header).expect("Failed to initialize VirtIO block device.");
+
+        Self(unsafe { UPCell::new(blk) })
+    }
+}
+
+impl BlockDevice for VirtIOBlock {
+    fn read_block(&self, block_id: usize, buf: &mut [u8]) {
+        self.0
+            .borrow_mut()
+            .read_block(block_id, buf)
+            .expect("Error when reading VirtIO block device.");
+    }
+
+    fn write_block(&self, block_id: usize, buf: &[u8]) {
+        self.0
+            .borrow_mut()
+            .write_block(block_id, buf)
+            .expect("Error when writing VirtIO block device.");
+    }
+}
+
+/// Memory services for the driver, where DMA areas are taken from the frame allocator.
+pub struct VirtIOHal;
+
+impl Hal for VirtIOHal {
+    fn dma_alloc(pages: usize) -> usize {
+        let frames = frame_alloc_contiguous(pages).expect("No contiguous frames left for DMA.");
+        let base = PhysAddr::from(frames[0].ppn).0;
+        QUEUE_FRAMES.borrow_mut().extend(frames);
+
+        base
+    }
+
+    fn dma_dealloc(paddr: usize, pages: usize) -> i32 {
+        let start = PhysAddr::from(paddr).floor().0;
+        // The trackers return their frames to the allocator when dropped.
+        QUEUE_FRAMES
+            .borrow_mut()
+            .retain(|frame| !(start..start + pages).contains(&frame.ppn.0));
+
+        0
+    }
+
+    /// Physical memory is identically mapped in the kernel space.
+    fn phys_to_virt(paddr: usize) -> usize {
+        paddr
+    }
+
+    /// Buffers may be on a kernel stack, which is not identically mapped,
+    /// so the address is translated through the kernel page table.
+    fn virt_to_phys(vaddr: usize) -> usize {
+        let va = VirtAddr::from(vaddr);
+        let ppn: PhysPageNum = KERNEL_SPACE
+            .borrow_mut()
+            .translate(va.floor())
+            .expect("Buffer of VirtIO block device is not mapped.")
+            .ppn();
+
+        PhysAddr::from(ppn).0 + va.page_offset()
+    }
+}
@@ -0,0 +1,276 @@
+use alloc::{sync::Arc, vec::Vec};
+use bitflags::bitflags;
+use easy_fs::{EasyFileSystem, FsError, FsResult, Inode, NAME_LENGTH_LIMIT};
+use lazy_static::lazy_static;
+use super::{File, SeekFrom, Stat, StatMode};
+use crate::{drivers::BLOCK_DEVICE, info, sync::UPCell};
+
+lazy_static! {
+    /// Root directory of the filesystem on the block device.
+    pub static ref ROOT_INODE: Arc<Inode> = {
+        let efs = EasyFileSystem::open(BLOCK_DEVICE.clone()).expect("Block device is not formatted by easy-fs.");
+        EasyFileSystem::root_inode(&efs)
+    };
+}
+
+/// Types of directory entries in `getdents64`, which share the values with Linux.
+const DT_DIR: u8 = 4;
+const DT_REG: u8 = 8;
+/// Offset of the name in a `linux_dirent64` record, after `d_ino`, `d_off`, `d_reclen` and `d_type`.
+const DIRENT_NAME_OFFSET: usize = 19;
+/// Size of the largest `linux_dirent64` record, whose name is NUL-terminated and padded to 8 bytes.
+pub const MAX_DIRENT_LEN: usize = (DIRENT_NAME_OFFSET + NAME_LENGTH_LIMIT + 1).next_multiple_of(8);
+
+bitflags! {
+    /// Flags of `openat`, which share the values with Linux.
+    /// A file is opened for reading only if neither `WRONLY` nor `RDWR` is set.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct OpenFlags: u32 {
+        const WRONLY = 1 << 0;
+        const RDWR = 1 << 1;
+        const CREAT = 1 << 6;
+        const TRUNC = 1 << 9;
+        const APPEND = 1 << 10;
+    }
+}
+
+impl OpenFlags {
+    /// Returns whether the file is opened for reading and writing respectively,
+    /// or `None` if both `WRONLY` and `RDWR` are set.
+    pub fn read_write(&self) -> Option<(bool, bool)> {
+        if self.contains(Self::WRONLY | Self::RDWR) {
+            None
+        } else if self.contains(Self::WRONLY) {
+            Some((false, true))
+        } else if self.contains(Self::RDWR) {
+            Some((true, true))
+        } else {
+            Some((true, false))
+        }
+    }
+}
+
+/// A file on the filesystem opened by a process, which keeps its own offset.
+pub struct OSInode {
+    readable: bool,
+    writable: bool,
+    /// Whether every write goes to the end of the file.
+    append: bool,
+    inner: UPCell<OSInodeInner>
+}
+
+struct OSInodeInner {
+    offset: usize,
+    inode: Arc<Inode>
+}
+
+impl OSInode {
+    pub fn new(readable: bool, writable: bool, append: bool, inode: Arc<Inode>) -> Self {
+        Self {
+            readable,
+            writable,
+            append,
+            inner: unsafe { UPCell::new(OSInodeInner { offset: 0, inode }) }
+        }
+    }
+
+    /// Reads from the current offset to the end of the file.
+    pub fn read_all(&self) -> Vec<u8> {
+        let mut inner = self.inner.borrow_mut();
+        let mut data = Vec::new();
+        let mut buffer = [0u8; 512];
+        loop {
+            let len = inner.inode.read_at(inner.offset, &mut buffer);
+            if len == 0 {
+                break;
+            }
+            inner.offset += len;
+            data.extend_from_slice(&buffer[..len]);
+        }
+
+        data
+    }
+}
+
+impl File for OSInode {
+    fn readable(&self) -> bool {
+        self.readable
+    }
+
+    fn writable(&self) -> bool {
+        self.writable
+    }
+
+    fn read(&self, buf: &mut [u8]) -> usize {
+        let mut inner = self.inner.borrow_mut();
+        // Directories are rejected by `sys_read`, and their entries are read by `read_dir`.
+        if inner.inode.is_dir() {
+            return 0;
+        }
+        let len = inner.inode.read_at(inner.offset, buf);
+        inner.offset += len;
+
+        len
+    }
+
+    fn write(&self, buf: &[u8]) -> Option<usize> {
+        let mut inner = self.inner.borrow_mut();
+        if self.append {
+            inner.offset = inner.inode.size();
+        }
+        let len = inner.inode.write_at(inner.offset, buf);
+        inner.offset += len;
+
+        Some(len)
+    }
+
+    fn seek(&self, pos: SeekFrom) -> Option<usize> {
+        let mut inner = self.inner.borrow_mut();
+        // The offset may go beyond the end, and the gap reads as zeros once written.
+        inner.offset = match pos {
+            SeekFrom::Start(offset) => offset,
+            SeekFrom::Current(delta) => inner.offset.checked_add_signed(delta)?,
+            SeekFrom::End(delta) => inner.inode.size().checked_add_signed(delta)?
+        };
+
+        Some(inner.offset)
+    }
+
+    fn stat(&self) -> Option<Stat> {
+        let inner = self.inner.borrow_mut();
+        let mode = if inner.inode.is_dir() {
+            StatMode::DIR
+        } else {
+            StatMode::FILE
+        };
+
+        Some(Stat::new(inner.inode.inode_id() as u64, mode, inner.inode.nlink()))
+    }
+
+    fn read_dir(&self, buf: &mut [u8]) -> Option<usize> {
+        let mut inner = self.inner.borrow_mut();
+        if !inner.inode.is_dir() {
+            return None;
+        }
+
+        let mut len = 0;
+        while let Some((entry, next_offset)) = inner.inode.next_entry(inner.offset) {
+            let name = entry.name.as_bytes();
+            let record_len = (DIRENT_NAME_OFFSET + name.len() + 1).next_multiple_of(8);
+            let Some(record) = buf.get_mut(len..len + record_len) else {
+                break;
+            };
+            record.fill(0);
+            record[0..8].copy_from_slice(&(entry.inode_id as u64).to_ne_bytes());
+            record[8..16].copy_from_slice(&(next_offset as i64).to_ne_bytes());
+            record[16..18].copy_from_slice(&(record_len as u16).to_ne_bytes());
+            record[18] = if entry.is_dir { DT_DIR } else { DT_REG };
+            record[DIRENT_NAME_OFFSET..DIRENT_NAME_OFFSET + name.len()].copy_from_slice(name);
+            len += record_len;
+            inner.offset = next_offset;
+        }
+
+        Some(len)
+    }
+}
+
+/// Finds the inode at `path`, which is resolved from the root directory
+/// whether it starts with `/` or not.
+fn find_path(path: &str) -> FsResult<Arc<Inode>> {
+    if path.is_empty() {
+        return Err(FsError::NotFound);
+    }
+
+    path.split('/')
+        .filter(|name| !name.is_empty())
+        .try_fold(ROOT_INODE.clone(), |dir, name| {
+            if !dir.is_dir() {
+                return Err(FsError::NotDir);
+            }
+            dir.find(name).ok_or(FsError::NotFound)
+        })
+}
+
+/// Finds the directory containing `path`, and returns it with the last name in `path`.
+fn find_parent(path: &str) -> FsResult<(Arc<Inode>, &str)> {
+    let path = path.trim_end_matches('/');
+    let (dir, name) = match path.rsplit_once('/') {
+        Some((dir, name)) => (find_path(if dir.is_empty() { "/" } else { dir })?, name),
+        None => (ROOT_INODE.clone(), path)
+    };
+    if !dir.is_dir() {
+        return Err(FsError::NotDir);
+    }
+
+    Ok((dir, name))
+}
+
+/// Opens the file at `path` with `flags`, which must have a valid access mode.
+/// Directories can only be opened for reading.
+pub fn open_file(path: &str, flags: OpenFlags) -> FsResult<Arc<OSInode>> {
+    let (readable, writable) = flags.read_write().expect("Invalid access mode of open flags.");
+    let inode = match find_path(path) {
+        Ok(inode) => {
+            if writable && inode.is_dir() {
+                return Err(FsError::IsDir);
+            }
+            if flags.contains(OpenFlags::TRUNC) && writable {
+                inode.clear();
+            }
+            inode
+        },
+        Err(FsError::NotFound) if flags.contains(OpenFlags::CREAT) => {
+            let (dir, name) = find_parent(path)?;
+            dir.create(name)?
+        },
+        Err(err) => return Err(err)
+    };
+
+    Ok(Arc::new(OSInode::new(readable, writable, flags.contains(OpenFlags::APPEND), inode)))
+}
+
+/// Creates a directory at `path`.
+pub fn make_dir(path: &str) -> FsResult<()> {
+    let (dir, name) = find_parent(path)?;
+    dir.mkdir(name)?;
+
+    Ok(())
+}
+
+/// Adds a hard link at `new_path` to the file at `old_path`.
+pub fn link_file(old_path: &str, new_path: &str) -> FsResult<()> {
+    let inode = find_path(old_path)?;
+    let (dir, name) = find_parent(new_path)?;
+
+    dir.link(name, &inode)
+}
+
+/// Removes the entry at `path`, which must be an empty directory if `remove_dir` is set,
+/// or a file otherwise. The file is freed once no entry links to it and it is closed.
+pub fn unlink_file(path: &str, remove_dir: bool) -> FsResult<()> {
+    let (dir, name) = find_parent(path)?;
+    if remove_dir {
+        dir.rmdir(name)
+    } else {
+        dir.unlink(name)
+    }
+}
+
+/// Prints the apps in the root directory.
+pub fn list_apps() {
+    info!("[kernel] /**** APPS ****");
+    for app in ROOT_INODE.ls().iter().filter(|name| *name != "." && *name != "..") {
+        info!("[kernel] {}", app);
+    }
+    info!("[kernel] **************/");
+}
+
+/// Reads the whole file at `path`, which must not be a directory.
+pub fn read_app(path: &str) -> FsResult<Vec<u8>> {
+    let file = open_file(path, OpenFlags::empty())?;
+    if file.inner.borrow_mut().inode.is_dir() {
+        return Err(FsError::IsDir);
+    }
+
+    Ok(file.read_all())
+}
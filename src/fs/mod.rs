@@ -0,0 +1,81 @@
+use bitflags::bitflags;
+
+// Include section.
+mod inode;
+mod pipe;
+mod stdio;
+
+// Export section.
+pub use inode::{MAX_DIRENT_LEN, OpenFlags, link_file, list_apps, make_dir, open_file, read_app, unlink_file};
+pub use pipe::make_pipe;
+pub use stdio::{Stdin, Stdout, Stderr};
+
+/// An object which can be read or written through a file descriptor.
+pub trait File: Send + Sync {
+    fn readable(&self) -> bool;
+    fn writable(&self) -> bool;
+    /// Reads into `buf`, and returns the number of bytes read.
+    fn read(&self, buf: &mut [u8]) -> usize;
+    /// Writes `buf`, and returns the number of bytes written.
+    /// Returns `None` if nothing can be written any more, e.g. a pipe with no read end left.
+    fn write(&self, buf: &[u8]) -> Option<usize>;
+
+    /// Moves the offset to `pos`, and returns the new offset. Returns `None` if the file
+    /// cannot be seeked, or the offset would be negative.
+    fn seek(&self, _pos: SeekFrom) -> Option<usize> {
+        None
+    }
+
+    /// Returns the status of the file, or `None` if it is not on the filesystem.
+    fn stat(&self) -> Option<Stat> {
+        None
+    }
+
+    /// Reads as many entries as fit in `buf` as `linux_dirent64` records from the offset,
+    /// and returns the number of bytes filled. Returns `None` if this is not a directory.
+    fn read_dir(&self, _buf: &mut [u8]) -> Option<usize> {
+        None
+    }
+}
+
+/// Position to seek to, as `whence` and `offset` of `lseek`.
+#[derive(Debug, Clone, Copy)]
+pub enum SeekFrom {
+    Start(usize),
+    Current(isize),
+    End(isize)
+}
+
+bitflags! {
+    /// Type of a file in [`Stat`], which shares the values with Linux.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct StatMode: u32 {
+        const DIR = 0o040000;
+        const FILE = 0o100000;
+    }
+}
+
+/// Status of a file, which shares the layout with `Stat` in the user library.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct Stat {
+    /// Id of the device, which is always 0 as there is only one.
+    pub dev: u64,
+    pub ino: u64,
+    pub mode: StatMode,
+    /// Number of hard links to the inode.
+    pub nlink: u32,
+    pad: [u64; 7]
+}
+
+impl Stat {
+    pub fn new(ino: u64, mode: StatMode, nlink: u32) -> Self {
+        Self {
+            dev: 0,
+            ino,
+            mode,
+            nlink,
+            pad: [0; 7]
+        }
+    }
+}
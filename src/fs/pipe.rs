@@ -0,0 +1,151 @@
+use super::File;
+use alloc::sync::{Arc, Weak};
+use crate::{sync::UPCell, task::suspend_current_and_run_next};
+
+const RING_BUFFER_SIZE: usize = 256;
+
+/// One end of a pipe, which is either readable or writable.
+pub struct Pipe {
+    readable: bool,
+    writable: bool,
+    buffer: Arc<UPCell<PipeRingBuffer>>
+}
+
+impl Pipe {
+    fn read_end_with_buffer(buffer: Arc<UPCell<PipeRingBuffer>>) -> Self {
+        Self { readable: true, writable: false, buffer }
+    }
+
+    fn write_end_with_buffer(buffer: Arc<UPCell<PipeRingBuffer>>) -> Self {
+        Self { readable: false, writable: true, buffer }
+    }
+}
+
+/// The buffer shared by both ends of a pipe, which only holds weak references to the ends,
+/// so that it can tell when either side is closed.
+struct PipeRingBuffer {
+    arr: [u8; RING_BUFFER_SIZE],
+    head: usize,
+    len: usize,
+    read_end: Weak<Pipe>,
+    write_end: Weak<Pipe>
+}
+
+impl PipeRingBuffer {
+    fn new() -> Self {
+        Self {
+            arr: [0; RING_BUFFER_SIZE],
+            head: 0,
+            len: 0,
+            read_end: Weak::new(),
+            write_end: Weak::new()
+        }
+    }
+
+    fn read_byte(&mut self) -> u8 {
+        let byte = self.arr[self.head];
+        self.head = (self.head + 1) % RING_BUFFER_SIZE;
+        self.len -= 1;
+
+        byte
+    }
+
+    fn write_byte(&mut self, byte: u8) {
+        self.arr[(self.head + self.len) % RING_BUFFER_SIZE] = byte;
+        self.len += 1;
+    }
+
+    fn available_write(&self) -> usize {
+        RING_BUFFER_SIZE - self.len
+    }
+
+    fn all_read_ends_closed(&self) -> bool {
+        self.read_end.upgrade().is_none()
+    }
+
+    fn all_write_ends_closed(&self) -> bool {
+        self.write_end.upgrade().is_none()
+    }
+}
+
+/// Creates a pipe, and returns its read end and write end.
+pub fn make_pipe() -> (Arc<Pipe>, Arc<Pipe>) {
+    let buffer = Arc::new(unsafe { UPCell::new(PipeRingBuffer::new()) });
+    let read_end = Arc::new(Pipe::read_end_with_buffer(buffer.clone()));
+    let write_end = Arc::new(Pipe::write_end_with_buffer(buffer.clone()));
+
+    let mut ring_buffer = buffer.borrow_mut();
+    ring_buffer.read_end = Arc::downgrade(&read_end);
+    ring_buffer.write_end = Arc::downgrade(&write_end);
+    drop(ring_buffer);
+
+    (read_end, write_end)
+}
+
+impl File for Pipe {
+    fn readable(&self) -> bool {
+        self.readable
+    }
+
+    fn writable(&self) -> bool {
+        self.writable
+    }
+
+    /// Blocks until some bytes arrive, and returns 0 for EOF once all write ends are closed.
+    fn read(&self, buf: &mut [u8]) -> usize {
+        assert!(self.readable);
+        if buf.is_empty() {
+            return 0;
+        }
+
+        loop {
+            let mut ring_buffer = self.buffer.borrow_mut();
+            if ring_buffer.len == 0 {
+                if ring_buffer.all_write_ends_closed() {
+                    return 0;
+                }
+
+                // The borrow must be released, as the writer needs the buffer.
+                drop(ring_buffer);
+                suspend_current_and_run_next();
+                continue;
+            }
+
+            let count = ring_buffer.len.min(buf.len());
+            for byte in buf[..count].iter_mut() {
+                *byte = ring_buffer.read_byte();
+            }
+
+            return count;
+        }
+    }
+
+    /// Blocks until all bytes are written, or all read ends are closed.
+    /// Returns the number of bytes written, or `None` if all read ends are closed before any.
+    fn write(&self, buf: &[u8]) -> Option<usize> {
+        assert!(self.writable);
+        let mut written = 0;
+
+        while written < buf.len() {
+            let mut ring_buffer = self.buffer.borrow_mut();
+            if ring_buffer.all_read_ends_closed() {
+                return if written == 0 { None } else { Some(written) };
+            }
+
+            let count = ring_buffer.available_write().min(buf.len() - written);
+            if count == 0 {
+                // The borrow must be released, as the reader needs the buffer.
+                drop(ring_buffer);
+                suspend_current_and_run_next();
+                continue;
+            }
+
+            for &byte in buf[written..written + count].iter() {
+                ring_buffer.write_byte(byte);
+            }
+            written += count;
+        }
+
+        Some(written)
+    }
+}
@@ -0,0 +1,98 @@
+use super::File;
+use crate::{print, sbi, task::suspend_current_and_run_next};
+
+/// Standard input, which reads from the console.
+pub struct Stdin;
+
+/// Standard output, which writes to the console.
+pub struct Stdout;
+
+/// Standard error, which writes to the console in red.
+pub struct Stderr;
+
+impl File for Stdin {
+    fn readable(&self) -> bool {
+        true
+    }
+
+    fn writable(&self) -> bool {
+        false
+    }
+
+    /// Blocks until at least one byte is read, and then takes the bytes
+    /// already available without blocking.
+    fn read(&self, buf: &mut [u8]) -> usize {
+        if buf.is_empty() {
+            return 0;
+        }
+
+        // Other processes run while waiting for the console.
+        buf[0] = loop {
+            match sbi::Stdin::read_byte() {
+                Some(byte) => break byte,
+                None => suspend_current_and_run_next()
+            }
+        };
+
+        let mut count = 1;
+        while count < buf.len() {
+            let Some(byte) = sbi::Stdin::read_byte() else {
+                break;
+            };
+            buf[count] = byte;
+            count += 1;
+        }
+
+        count
+    }
+
+    fn write(&self, _buf: &[u8]) -> Option<usize> {
+        panic!("Cannot write to stdin.");
+    }
+}
+
+impl File for Stdout {
+    fn readable(&self) -> bool {
+        false
+    }
+
+    fn writable(&self) -> bool {
+        true
+    }
+
+    fn read(&self, _buf: &mut [u8]) -> usize {
+        panic!("Cannot read from stdout.");
+    }
+
+    fn write(&self, buf: &[u8]) -> Option<usize> {
+        // Bytes are written one by one, since they may not be valid UTF-8.
+        for &byte in buf {
+            sbi::Stdout::write_byte(byte).unwrap();
+        }
+
+        Some(buf.len())
+    }
+}
+
+impl File for Stderr {
+    fn readable(&self) -> bool {
+        false
+    }
+
+    fn writable(&self) -> bool {
+        true
+    }
+
+    fn read(&self, _buf: &mut [u8]) -> usize {
+        panic!("Cannot read from stderr.");
+    }
+
+    /// Uses the same ANSI color codes as `error_print!`.
+    fn write(&self, buf: &[u8]) -> Option<usize> {
+        print!("\x1b[091m");
+        Stdout.write(buf);
+        print!("\x1b[037m");
+
+        Some(buf.len())
+    }
+}
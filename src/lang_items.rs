@@ -1,6 +1,7 @@
-use core::panic::PanicInfo;
+use core::{alloc::Layout, panic::PanicInfo};
 use crate::{
     syscall::print_stack_trace,
+    mm::heap_usage,
     error, error_print, info_print, println, shutdown
 };
 
@@ -35,3 +36,16 @@ pub fn handle_panic(info: &PanicInfo) -> ! {
     
     shutdown!(true);
 }
+
+#[alloc_error_handler]
+pub fn handle_alloc_error(layout: Layout) -> ! {
+    let (used, total) = heap_usage();
+    error_print!("Kernel heap exhausted on allocating [");
+    info_print!("{} bytes, aligned to {}", layout.size(), layout.align());
+    error!("].");
+    error_print!("Heap usage: [");
+    info_print!("{} / {} bytes", used, total);
+    error!("].");
+
+    panic!("Out of kernel heap memory.");
+}
@@ -1,26 +1,35 @@
 #![no_std]
 #![no_main]
-#![feature(custom_test_frameworks)]
+#![feature(custom_test_frameworks, alloc_error_handler)]
 #![test_runner(crate::test_runner)]
 #![reexport_test_harness_main = "test_main"]
+extern crate alloc;
+
 mod lang_items;
-mod batch;
 
 #[macro_use]
 pub mod sbi;
 pub mod sync;
 mod trap;
 mod syscall;
+mod task;
+mod timer;
+mod mm;
+mod fs;
+mod drivers;
 
 pub use lang_items::handle_panic;
 pub use sbi::*;
-pub use trap::init as trap_init;
-pub use batch::{init as batch_init, print_app_info, run_next_app};
+pub use trap::{init as trap_init, enable_timer_interrupt};
+pub use mm::init as mm_init;
+pub use timer::set_next_trigger;
+pub use fs::list_apps;
+pub use task::{init as task_init, run_processes};
 
 pub fn clear_bss() {
     unsafe extern "C" {
-        fn sbss();
-        fn ebss();
+        safe fn sbss();
+        safe fn ebss();
     }
 
     for addr in sbss as usize..ebss as usize {
@@ -4,14 +4,17 @@ use core::arch::global_asm;
 use os::*;
 
 global_asm!(include_str!("entry.asm"));
-global_asm!(include_str!("link_app.S"));
 
 /// This main function is only used for executing some regular jobs.
 /// It must be called within [`rust_main`].
 fn main() {
+    mm_init();
     trap_init();
-    batch_init();
-    run_next_app();
+    list_apps();
+    task_init();
+    enable_timer_interrupt();
+    set_next_trigger();
+    run_processes();
 }
 
 /// The very entry point of Rust program.
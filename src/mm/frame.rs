@@ -0,0 +1,71 @@
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
+use frame_allocator::{FrameAllocator, StackFrameAllocator};
+use crate::sync::UPCell;
+use super::{PhysAddr, PhysPageNum};
+
+/// End of the physical memory on qemu `virt` machine with default 128 MiB RAM.
+pub const MEMORY_END: usize = 0x88000000;
+
+static FRAME_ALLOCATOR: UPCell<StackFrameAllocator> = unsafe {
+    UPCell::new(StackFrameAllocator::new())
+};
+
+/// An RAII handle of an allocated frame, which is zeroed on allocation
+/// and returned to the allocator on drop.
+pub struct FrameTracker {
+    pub ppn: PhysPageNum
+}
+
+impl FrameTracker {
+    pub fn new(ppn: PhysPageNum) -> Self {
+        ppn.get_bytes_array().fill(0);
+        Self { ppn }
+    }
+}
+
+impl Debug for FrameTracker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "FrameTracker:PPN={:#x}", self.ppn.0)
+    }
+}
+
+impl Drop for FrameTracker {
+    fn drop(&mut self) {
+        frame_dealloc(self.ppn);
+    }
+}
+
+pub fn init_frame_allocator() {
+    unsafe extern "C" {
+        safe fn ekernel();
+    }
+
+    FRAME_ALLOCATOR.borrow_mut().init(
+        PhysAddr::from(ekernel as usize).ceil().0,
+        PhysAddr::from(MEMORY_END).floor().0
+    );
+}
+
+/// Allocates a zeroed frame, or returns `None` if the memory is used up.
+pub fn frame_alloc() -> Option<FrameTracker> {
+    FRAME_ALLOCATOR
+        .borrow_mut()
+        .alloc()
+        .map(|ppn| FrameTracker::new(ppn.into()))
+}
+
+/// Allocates `count` zeroed frames which are physically contiguous, e.g. for DMA,
+/// or returns `None` if there is no such space.
+pub fn frame_alloc_contiguous(count: usize) -> Option<Vec<FrameTracker>> {
+    let base = FRAME_ALLOCATOR.borrow_mut().alloc_contiguous(count)?;
+    let frames = (base..base + count)
+        .map(|ppn| FrameTracker::new(ppn.into()))
+        .collect();
+
+    Some(frames)
+}
+
+fn frame_dealloc(ppn: PhysPageNum) {
+    FRAME_ALLOCATOR.borrow_mut().dealloc(ppn.0);
+}
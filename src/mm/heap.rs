@@ -0,0 +1,23 @@
+use buddy_system_allocator::LockedHeap;
+
+/// Size of the kernel heap, which is a static region in `.bss`.
+const KERNEL_HEAP_SIZE: usize = 0x300000;
+
+#[global_allocator]
+static HEAP_ALLOCATOR: LockedHeap<32> = LockedHeap::empty();
+
+static mut HEAP_SPACE: [u8; KERNEL_HEAP_SIZE] = [0; KERNEL_HEAP_SIZE];
+
+pub fn init_heap() {
+    unsafe {
+        HEAP_ALLOCATOR
+            .lock()
+            .init(&raw mut HEAP_SPACE as usize, KERNEL_HEAP_SIZE);
+    }
+}
+
+/// Returns the bytes allocated from the kernel heap, and the total bytes of it.
+pub fn heap_usage() -> (usize, usize) {
+    let heap = HEAP_ALLOCATOR.lock();
+    (heap.stats_alloc_actual(), heap.stats_total_bytes())
+}
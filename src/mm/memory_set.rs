@@ -0,0 +1,376 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::arch::asm;
+use crate::{info, sync::UPCell};
+use super::{
+    frame_alloc, FrameTracker, MEMORY_END,
+    PageTable, PageTableEntry, PTEFlags,
+    PhysAddr, PhysPageNum, VirtAddr, VirtPageNum, VPNRange, StepByOne, PAGE_SIZE, USER_SPACE_END
+};
+use bitflags::bitflags;
+use lazy_static::lazy_static;
+use riscv::register::satp;
+use xmas_elf::{ElfFile, header::Machine, program::Type};
+
+/// The highest page of every address space, where the trap entry code is mapped.
+pub const TRAMPOLINE: usize = usize::MAX - PAGE_SIZE + 1;
+/// The page below [`TRAMPOLINE`] in user space, where the trap context of an app is stored.
+pub const TRAP_CONTEXT: usize = TRAMPOLINE - PAGE_SIZE;
+pub const USER_STACK_SIZE: usize = 4096 * 2;
+/// Registers of devices on qemu `virt` machine as `(start, size)`, which the kernel maps.
+pub const MMIO: &[(usize, usize)] = &[
+    // VirtIO block device.
+    (0x10001000, 0x1000)
+];
+
+lazy_static! {
+    pub static ref KERNEL_SPACE: UPCell<MemorySet> = unsafe {
+        UPCell::new(MemorySet::new_kernel())
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapType {
+    /// Virtual pages are mapped to the physical ones with the same numbers.
+    Identical,
+    /// Virtual pages are mapped to newly allocated frames.
+    Framed
+}
+
+bitflags! {
+    /// A subset of [`PTEFlags`], which shares the same bits.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct MapPermission: u8 {
+        const R = 1 << 1;
+        const W = 1 << 2;
+        const X = 1 << 3;
+        const U = 1 << 4;
+    }
+}
+
+/// A contiguous range of virtual pages with the same mapping type and permission.
+pub struct MapArea {
+    vpn_range: VPNRange,
+    /// Frames of a framed area, which are recycled along with the area.
+    data_frames: BTreeMap<VirtPageNum, FrameTracker>,
+    map_type: MapType,
+    map_perm: MapPermission
+}
+
+impl MapArea {
+    pub fn new(start_va: VirtAddr, end_va: VirtAddr, map_type: MapType, map_perm: MapPermission) -> Self {
+        Self {
+            vpn_range: VPNRange::new(start_va.floor(), end_va.ceil()),
+            data_frames: BTreeMap::new(),
+            map_type,
+            map_perm
+        }
+    }
+
+    /// Creates an empty area with the same range, type and permission as `another`.
+    pub fn from_another(another: &Self) -> Self {
+        Self {
+            vpn_range: another.vpn_range,
+            data_frames: BTreeMap::new(),
+            map_type: another.map_type,
+            map_perm: another.map_perm
+        }
+    }
+
+    /// Returns whether the area shares any page with `other`.
+    fn overlaps(&self, other: &Self) -> bool {
+        self.vpn_range.get_start() < other.vpn_range.get_end()
+            && other.vpn_range.get_start() < self.vpn_range.get_end()
+    }
+
+    /// Maps a page of the area, or returns `None` if no frame is left.
+    fn map_one(&mut self, page_table: &mut PageTable, vpn: VirtPageNum) -> Option<()> {
+        let flags = PTEFlags::from_bits_truncate(self.map_perm.bits());
+        match self.map_type {
+            MapType::Identical => page_table.map(vpn, PhysPageNum(vpn.0), flags),
+            MapType::Framed => {
+                let frame = frame_alloc()?;
+                page_table.map(vpn, frame.ppn, flags)?;
+                self.data_frames.insert(vpn, frame);
+
+                Some(())
+            }
+        }
+    }
+
+    fn unmap_one(&mut self, page_table: &mut PageTable, vpn: VirtPageNum) {
+        if self.map_type == MapType::Framed {
+            self.data_frames.remove(&vpn);
+        }
+        page_table.unmap(vpn);
+    }
+
+    /// Maps all pages of the area, or returns `None` if frames run out,
+    /// where the pages mapped so far are unmapped again.
+    pub fn map(&mut self, page_table: &mut PageTable) -> Option<()> {
+        for vpn in self.vpn_range {
+            if self.map_one(page_table, vpn).is_none() {
+                for mapped_vpn in VPNRange::new(self.vpn_range.get_start(), vpn) {
+                    self.unmap_one(page_table, mapped_vpn);
+                }
+                return None;
+            }
+        }
+
+        Some(())
+    }
+
+    pub fn unmap(&mut self, page_table: &mut PageTable) {
+        for vpn in self.vpn_range {
+            self.unmap_one(page_table, vpn);
+        }
+    }
+
+    /// Copies `data` to the area, starting at `offset` bytes from the beginning of its first page.
+    /// The area must be mapped in `page_table` before.
+    pub fn copy_data(&self, page_table: &PageTable, data: &[u8], offset: usize) {
+        let mut vpn = self.vpn_range.get_start();
+        let mut start = 0;
+        let mut page_offset = offset;
+        while start < data.len() {
+            let len = (PAGE_SIZE - page_offset).min(data.len() - start);
+            let dst = &mut page_table
+                .translate(vpn)
+                .unwrap()
+                .ppn()
+                .get_bytes_array()[page_offset..page_offset + len];
+            dst.copy_from_slice(&data[start..start + len]);
+
+            start += len;
+            page_offset = 0;
+            vpn.step();
+        }
+    }
+}
+
+/// An address space, which consists of a page table and the areas mapped in it.
+pub struct MemorySet {
+    page_table: PageTable,
+    areas: Vec<MapArea>
+}
+
+impl MemorySet {
+    pub fn new_bare() -> Self {
+        Self {
+            page_table: PageTable::new(),
+            areas: Vec::new()
+        }
+    }
+
+    pub fn token(&self) -> usize {
+        self.page_table.token()
+    }
+
+    /// Maps the area, and copies `data` to it if there is any.
+    /// Returns `None` if frames run out, where nothing is mapped.
+    fn push(&mut self, mut map_area: MapArea, data: Option<(&[u8], usize)>) -> Option<()> {
+        map_area.map(&mut self.page_table)?;
+        if let Some((data, offset)) = data {
+            map_area.copy_data(&self.page_table, data, offset);
+        }
+        self.areas.push(map_area);
+
+        Some(())
+    }
+
+    /// Maps a framed area in `[start_va, end_va)`, which is not overlapped with any other.
+    pub fn insert_framed_area(&mut self, start_va: VirtAddr, end_va: VirtAddr, permission: MapPermission) {
+        self.push(MapArea::new(start_va, end_va, MapType::Framed, permission), None)
+            .expect("No frame left for a framed area.");
+    }
+
+    /// Unmaps the area starting at `start_vpn` and recycles its frames, if there is one.
+    pub fn remove_area_with_start_vpn(&mut self, start_vpn: VirtPageNum) {
+        if let Some(idx) = self.areas
+            .iter()
+            .position(|area| area.vpn_range.get_start() == start_vpn)
+        {
+            let mut area = self.areas.remove(idx);
+            area.unmap(&mut self.page_table);
+        }
+    }
+
+    /// Maps the trampoline, which is not recorded as an area since it is shared by all spaces.
+    fn map_trampoline(&mut self) {
+        unsafe extern "C" {
+            safe fn strampoline();
+        }
+
+        self.page_table
+            .map(
+                VirtAddr::from(TRAMPOLINE).into(),
+                PhysAddr::from(strampoline as usize).into(),
+                PTEFlags::R | PTEFlags::X
+            )
+            .expect("No frame left for the trampoline.");
+    }
+
+    /// Creates the kernel space, where the kernel image and the rest physical memory
+    /// are identically mapped.
+    pub fn new_kernel() -> Self {
+        unsafe extern "C" {
+            safe fn stext();
+            safe fn etext();
+            safe fn srodata();
+            safe fn erodata();
+            safe fn sdata();
+            safe fn edata();
+            safe fn sbss_with_stack();
+            safe fn ebss();
+            safe fn ekernel();
+        }
+
+        let mut memory_set = Self::new_bare();
+        memory_set.map_trampoline();
+
+        let areas = [
+            (".text", stext as usize, etext as usize, MapPermission::R | MapPermission::X),
+            (".rodata", srodata as usize, erodata as usize, MapPermission::R),
+            (".data", sdata as usize, edata as usize, MapPermission::R | MapPermission::W),
+            (".bss", sbss_with_stack as usize, ebss as usize, MapPermission::R | MapPermission::W),
+            ("physical memory", ekernel as usize, MEMORY_END, MapPermission::R | MapPermission::W)
+        ];
+        for (name, start, end, perm) in areas {
+            info!("[kernel] Mapping {} [{:#x}, {:#x})", name, start, end);
+            memory_set
+                .push(MapArea::new(start.into(), end.into(), MapType::Identical, perm), None)
+                .expect("No frame left for the kernel space.");
+        }
+
+        // Device registers are accessed through identical mapping as well.
+        for &(start, size) in MMIO {
+            info!("[kernel] Mapping MMIO [{:#x}, {:#x})", start, start + size);
+            let perm = MapPermission::R | MapPermission::W;
+            memory_set
+                .push(MapArea::new(start.into(), (start + size).into(), MapType::Identical, perm), None)
+                .expect("No frame left for the kernel space.");
+        }
+
+        memory_set
+    }
+
+    /// Creates a user space from the ELF file of an app, where the trampoline, trap context
+    /// and user stack are also mapped. Returns the space, user stack top and entry point,
+    /// or `None` if the ELF file is not for RISC-V, its segments are out of the file,
+    /// beyond the user half or overlapped, or frames run out.
+    pub fn from_elf(elf_data: &[u8]) -> Option<(Self, usize, usize)> {
+        let elf = ElfFile::new(elf_data).ok()?;
+        if elf.header.pt2.machine().as_machine() != Machine::RISC_V {
+            return None;
+        }
+
+        let mut memory_set = Self::new_bare();
+        memory_set.map_trampoline();
+
+        let mut max_end_vpn = VirtPageNum(0);
+        for ph in elf.program_iter() {
+            if ph.get_type() != Ok(Type::Load) {
+                continue;
+            }
+
+            let start = ph.virtual_addr() as usize;
+            let end = start.checked_add(ph.mem_size() as usize)?;
+            let offset = ph.offset() as usize;
+            let file_size = ph.file_size() as usize;
+            let file_end = offset.checked_add(file_size)?;
+            if end > USER_SPACE_END || file_size > ph.mem_size() as usize || file_end > elf_data.len() {
+                return None;
+            }
+            let start_va: VirtAddr = start.into();
+            let end_va: VirtAddr = end.into();
+
+            let mut map_perm = MapPermission::U;
+            let ph_flags = ph.flags();
+            if ph_flags.is_read() {
+                map_perm |= MapPermission::R;
+            }
+            if ph_flags.is_write() {
+                map_perm |= MapPermission::W;
+            }
+            if ph_flags.is_execute() {
+                map_perm |= MapPermission::X;
+            }
+
+            // Bytes beyond the file size, which is `.bss` in general, are left zero.
+            let map_area = MapArea::new(start_va, end_va, MapType::Framed, map_perm);
+            if memory_set.areas.iter().any(|area| area.overlaps(&map_area)) {
+                return None;
+            }
+            max_end_vpn = max_end_vpn.max(map_area.vpn_range.get_end());
+            memory_set.push(
+                map_area,
+                Some((&elf_data[offset..file_end], start_va.page_offset()))
+            )?;
+        }
+
+        // A guard page is left unmapped below the user stack, so that overflows fault.
+        let max_end_va: VirtAddr = max_end_vpn.into();
+        let user_stack_bottom = usize::from(max_end_va) + PAGE_SIZE;
+        let user_stack_top = user_stack_bottom + USER_STACK_SIZE;
+        if user_stack_top > USER_SPACE_END {
+            return None;
+        }
+        memory_set.push(
+            MapArea::new(
+                user_stack_bottom.into(),
+                user_stack_top.into(),
+                MapType::Framed,
+                MapPermission::R | MapPermission::W | MapPermission::U
+            ),
+            None
+        )?;
+        memory_set.push(
+            MapArea::new(
+                TRAP_CONTEXT.into(),
+                TRAMPOLINE.into(),
+                MapType::Framed,
+                MapPermission::R | MapPermission::W
+            ),
+            None
+        )?;
+
+        Some((memory_set, user_stack_top, elf.header.pt2.entry_point() as usize))
+    }
+
+    /// Creates a copy of a user space, where every framed page is copied to a new frame.
+    pub fn from_existed_user(user_space: &Self) -> Self {
+        let mut memory_set = Self::new_bare();
+        memory_set.map_trampoline();
+
+        for area in user_space.areas.iter() {
+            memory_set
+                .push(MapArea::from_another(area), None)
+                .expect("No frame left to copy a user space.");
+            for vpn in area.vpn_range {
+                let src_ppn = user_space.translate(vpn).unwrap().ppn();
+                let dst_ppn = memory_set.translate(vpn).unwrap().ppn();
+                dst_ppn.get_bytes_array().copy_from_slice(src_ppn.get_bytes_array());
+            }
+        }
+
+        memory_set
+    }
+
+    /// Switches to this address space by writing its token into `satp`.
+    pub fn activate(&self) {
+        let satp = self.page_table.token();
+        unsafe {
+            satp::write(satp);
+            asm!("sfence.vma");
+        }
+    }
+
+    pub fn translate(&self, vpn: VirtPageNum) -> Option<PageTableEntry> {
+        self.page_table.translate(vpn)
+    }
+
+    /// Recycles the frames of all areas, while the page table is kept until the space is dropped.
+    /// It must not be called on an active space.
+    pub fn recycle_data_pages(&mut self) {
+        self.areas.clear();
+    }
+}
@@ -0,0 +1,22 @@
+// Include section.
+mod address;
+mod heap;
+mod frame;
+mod page_table;
+mod memory_set;
+mod user_buffer;
+
+// Export section.
+pub use address::*;
+pub use heap::heap_usage;
+pub use frame::{frame_alloc, frame_alloc_contiguous, FrameTracker, MEMORY_END};
+pub use page_table::{PageTable, PageTableEntry, PTEFlags, translated_byte_buffer, translated_cstr, translated_ref, translated_refmut};
+pub use user_buffer::{UserBuffer, copy_to_user};
+pub use memory_set::{KERNEL_SPACE, MapPermission, MemorySet, TRAMPOLINE, TRAP_CONTEXT};
+
+/// Initializes the kernel heap and frame allocator, and enables Sv39 paging with the kernel space.
+pub fn init() {
+    heap::init_heap();
+    frame::init_frame_allocator();
+    KERNEL_SPACE.borrow_mut().activate();
+}
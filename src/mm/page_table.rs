@@ -0,0 +1,223 @@
+use alloc::{vec, vec::Vec};
+use bitflags::bitflags;
+use super::{frame_alloc, FrameTracker, PhysPageNum, VirtAddr, VirtPageNum, PAGE_SIZE, USER_SPACE_END};
+
+/// Longest string accepted from user space, excluding the trailing NUL byte.
+pub const MAX_USER_STR_LEN: usize = 4096;
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct PTEFlags: u8 {
+        const V = 1 << 0;
+        const R = 1 << 1;
+        const W = 1 << 2;
+        const X = 1 << 3;
+        const U = 1 << 4;
+        const G = 1 << 5;
+        const A = 1 << 6;
+        const D = 1 << 7;
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct PageTableEntry {
+    pub bits: usize
+}
+
+impl PageTableEntry {
+    pub fn new(ppn: PhysPageNum, flags: PTEFlags) -> Self {
+        Self { bits: ppn.0 << 10 | flags.bits() as usize }
+    }
+
+    pub fn empty() -> Self {
+        Self { bits: 0 }
+    }
+
+    pub fn ppn(&self) -> PhysPageNum {
+        (self.bits >> 10 & ((1usize << 44) - 1)).into()
+    }
+
+    pub fn flags(&self) -> PTEFlags {
+        PTEFlags::from_bits_truncate(self.bits as u8)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.flags().contains(PTEFlags::V)
+    }
+}
+
+/// A 3-level Sv39 page table, which is identified by the frame of its root node.
+pub struct PageTable {
+    root_ppn: PhysPageNum,
+    /// Frames of the nodes, which are recycled along with the page table.
+    frames: Vec<FrameTracker>
+}
+
+impl PageTable {
+    pub fn new() -> Self {
+        let frame = frame_alloc().expect("No frame left for a page table.");
+        Self {
+            root_ppn: frame.ppn,
+            frames: vec![frame]
+        }
+    }
+
+    /// Borrows a page table from the value of `satp`, e.g. the one of an app.
+    /// It owns no frame, so it is only used for translation.
+    pub fn from_token(satp: usize) -> Self {
+        Self {
+            root_ppn: PhysPageNum::from(satp & ((1usize << 44) - 1)),
+            frames: Vec::new()
+        }
+    }
+
+    /// Finds the leaf entry of given page, and creates the missing nodes on the path.
+    fn find_pte_create(&mut self, vpn: VirtPageNum) -> Option<&mut PageTableEntry> {
+        let idxs = vpn.indexes();
+        let mut ppn = self.root_ppn;
+        for (i, &idx) in idxs.iter().enumerate() {
+            let pte = &mut ppn.get_pte_array()[idx];
+            if i == 2 {
+                return Some(pte);
+            }
+
+            if !pte.is_valid() {
+                let frame = frame_alloc()?;
+                *pte = PageTableEntry::new(frame.ppn, PTEFlags::V);
+                self.frames.push(frame);
+            }
+            ppn = pte.ppn();
+        }
+
+        None
+    }
+
+    /// Finds the leaf entry of given page, without creating any node.
+    fn find_pte(&self, vpn: VirtPageNum) -> Option<&mut PageTableEntry> {
+        let idxs = vpn.indexes();
+        let mut ppn = self.root_ppn;
+        for (i, &idx) in idxs.iter().enumerate() {
+            let pte = &mut ppn.get_pte_array()[idx];
+            if i == 2 {
+                return Some(pte);
+            }
+
+            if !pte.is_valid() {
+                return None;
+            }
+            ppn = pte.ppn();
+        }
+
+        None
+    }
+
+    /// Maps `vpn` to `ppn`, or returns `None` if no frame is left for a page table.
+    pub fn map(&mut self, vpn: VirtPageNum, ppn: PhysPageNum, flags: PTEFlags) -> Option<()> {
+        let pte = self.find_pte_create(vpn)?;
+        assert!(!pte.is_valid(), "{:?} is mapped before mapping.", vpn);
+        *pte = PageTableEntry::new(ppn, flags | PTEFlags::V);
+
+        Some(())
+    }
+
+    pub fn unmap(&mut self, vpn: VirtPageNum) {
+        let pte = self.find_pte(vpn).expect("Page table node is missing when unmapping.");
+        assert!(pte.is_valid(), "{:?} is invalid before unmapping.", vpn);
+        *pte = PageTableEntry::empty();
+    }
+
+    pub fn translate(&self, vpn: VirtPageNum) -> Option<PageTableEntry> {
+        self.find_pte(vpn)
+            .filter(|pte| pte.is_valid())
+            .map(|pte| *pte)
+    }
+
+    /// Returns the value of `satp` which enables Sv39 mode with this page table.
+    pub fn token(&self) -> usize {
+        8usize << 60 | self.root_ppn.0
+    }
+}
+
+/// Returns the pieces of a user buffer with given token, which may span several discontiguous frames.
+/// Returns `None` if any page of the buffer is not accessible from user mode with `access`,
+/// or the buffer goes beyond the user half of the address space.
+pub fn translated_byte_buffer(
+    token: usize,
+    ptr: *const u8,
+    len: usize,
+    access: PTEFlags
+) -> Option<Vec<&'static mut [u8]>> {
+    let page_table = PageTable::from_token(token);
+    let mut start = ptr as usize;
+    let end = start.checked_add(len)?;
+    // `VirtAddr` keeps only the low bits, so a higher address would alias a user page.
+    if end > USER_SPACE_END {
+        return None;
+    }
+
+    let mut buffers = Vec::new();
+    while start < end {
+        let start_va = VirtAddr::from(start);
+        let ppn = page_table
+            .translate(start_va.floor())
+            .filter(|pte| pte.flags().contains(access | PTEFlags::U))?
+            .ppn();
+        let chunk_end = ((start & !(PAGE_SIZE - 1)) + PAGE_SIZE).min(end);
+
+        let offset = start_va.page_offset();
+        buffers.push(&mut ppn.get_bytes_array()[offset..offset + chunk_end - start]);
+        start = chunk_end;
+    }
+
+    Some(buffers)
+}
+
+/// Copies the bytes of a NUL-terminated string from the user space with given token, which is
+/// at most [`MAX_USER_STR_LEN`] bytes long, without the NUL byte. Returns `None` if it is not
+/// readable by the user or too long.
+pub fn translated_cstr(token: usize, ptr: *const u8) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let mut va = ptr as usize;
+    loop {
+        // The string is checked page by page, as its length is unknown in advance.
+        let page_len = PAGE_SIZE - VirtAddr::from(va).page_offset();
+        let page = translated_byte_buffer(token, va as *const u8, page_len, PTEFlags::R)?;
+        for &byte in page.iter().flat_map(|buffer| buffer.iter()) {
+            if byte == 0 {
+                return Some(bytes);
+            }
+            if bytes.len() == MAX_USER_STR_LEN {
+                return None;
+            }
+            bytes.push(byte);
+        }
+        va += page_len;
+    }
+}
+
+/// Returns a kernel reference to a value in the user space with given token, or `None`
+/// if it is misaligned, spans two pages or is not readable by the user.
+pub fn translated_ref<T>(token: usize, ptr: *const T) -> Option<&'static T> {
+    if !ptr.is_aligned() || VirtAddr::from(ptr as usize).page_offset() + size_of::<T>() > PAGE_SIZE {
+        return None;
+    }
+
+    let buffers = translated_byte_buffer(token, ptr as *const u8, size_of::<T>(), PTEFlags::R)?;
+    let value = buffers.into_iter().next()?.as_ptr() as *const T;
+
+    Some(unsafe { &*value })
+}
+
+/// Returns a kernel reference to a value in the user space with given token, or `None`
+/// if it is misaligned, spans two pages or is not writable by the user.
+pub fn translated_refmut<T>(token: usize, ptr: *mut T) -> Option<&'static mut T> {
+    if !ptr.is_aligned() || VirtAddr::from(ptr as usize).page_offset() + size_of::<T>() > PAGE_SIZE {
+        return None;
+    }
+
+    let buffers = translated_byte_buffer(token, ptr as *const u8, size_of::<T>(), PTEFlags::R | PTEFlags::W)?;
+    let value = buffers.into_iter().next()?.as_mut_ptr() as *mut T;
+
+    Some(unsafe { &mut *value })
+}
@@ -0,0 +1,58 @@
+use alloc::vec::Vec;
+use super::{PTEFlags, translated_byte_buffer};
+
+/// A buffer in user space, which is validated and split into kernel-accessible pieces.
+pub struct UserBuffer {
+    pub buffers: Vec<&'static mut [u8]>
+}
+
+impl UserBuffer {
+    /// Validates `[ptr, ptr + len)` in the user space with given token, which must be readable
+    /// from user mode, and also writable if `writable` is set. Returns `None` otherwise.
+    pub fn new(token: usize, ptr: *const u8, len: usize, writable: bool) -> Option<Self> {
+        let access = if writable { PTEFlags::R | PTEFlags::W } else { PTEFlags::R };
+        let buffers = translated_byte_buffer(token, ptr, len, access)?;
+
+        Some(Self { buffers })
+    }
+
+    /// Copies the beginning of the buffer into `data`, and returns the number of bytes copied.
+    pub fn read(&self, data: &mut [u8]) -> usize {
+        let mut start = 0;
+        for buffer in self.buffers.iter() {
+            if start == data.len() {
+                break;
+            }
+
+            let len = buffer.len().min(data.len() - start);
+            data[start..start + len].copy_from_slice(&buffer[..len]);
+            start += len;
+        }
+
+        start
+    }
+
+    /// Copies `data` to the beginning of the buffer, and returns the number of bytes copied.
+    pub fn fill(&mut self, data: &[u8]) -> usize {
+        let mut start = 0;
+        for buffer in self.buffers.iter_mut() {
+            if start == data.len() {
+                break;
+            }
+
+            let len = buffer.len().min(data.len() - start);
+            buffer[..len].copy_from_slice(&data[start..start + len]);
+            start += len;
+        }
+
+        start
+    }
+}
+
+/// Copies `data` to user space, or returns `None` if the range is not writable by the user.
+pub fn copy_to_user(token: usize, ptr: *mut u8, data: &[u8]) -> Option<()> {
+    let mut buffer = UserBuffer::new(token, ptr, data.len(), true)?;
+    buffer.fill(data);
+
+    Some(())
+}
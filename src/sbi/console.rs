@@ -4,9 +4,10 @@ use core::fmt::{Write, Result as FmtResult, Error as FmtError, Arguments};
 pub struct Stdin;
 
 impl Stdin {
+    /// Returns the next byte from the console, or `None` if no byte is available yet.
     #[allow(deprecated)]
-    pub fn read_byte() -> usize {
-        console_getchar()
+    pub fn read_byte() -> Option<u8> {
+        u8::try_from(console_getchar()).ok()
     }
 }
 
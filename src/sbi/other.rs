@@ -9,4 +9,9 @@ pub fn _shutdown(failure: bool) -> ! {
     }
 
     unreachable!()
-}
\ No newline at end of file
+}
+
+/// Programs the clock for next timer interrupt, at the given value of `time` CSR.
+pub fn set_timer(stime_value: usize) {
+    sbi_rt::set_timer(stime_value as u64);
+}
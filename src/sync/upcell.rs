@@ -9,7 +9,7 @@ pub struct UniProcessorCell<T> {
 unsafe impl<T> Sync for UPCell<T> {}
 
 impl<T> UPCell<T> {
-    pub unsafe fn new(item: T) -> Self {
+    pub const unsafe fn new(item: T) -> Self {
         Self { inner: RefCell::new(item) }
     }
 
@@ -1,37 +1,71 @@
-use crate::{
-    print, info,
-    batch::run_next_app
-};
-
-const STDOUT: usize = 1;
+use super::{SysError, fs::*, process::*};
+use crate::{fs::Stat, timer::TimeVal, warn};
 
+const SYSCALL_DUP: usize = 24;
+const SYSCALL_MKDIRAT: usize = 34;
+const SYSCALL_UNLINKAT: usize = 35;
+const SYSCALL_LINKAT: usize = 37;
+const SYSCALL_OPENAT: usize = 56;
+const SYSCALL_CLOSE: usize = 57;
+const SYSCALL_PIPE: usize = 59;
+const SYSCALL_GETDENTS64: usize = 61;
+const SYSCALL_LSEEK: usize = 62;
+const SYSCALL_READ: usize = 63;
 const SYSCALL_WRITE: usize = 64;
+const SYSCALL_FSTAT: usize = 80;
 const SYSCALL_EXIT: usize = 93;
+const SYSCALL_YIELD: usize = 124;
+const SYSCALL_GET_TIME: usize = 169;
+const SYSCALL_GETPID: usize = 172;
+const SYSCALL_FORK: usize = 220;
+const SYSCALL_EXEC: usize = 221;
+const SYSCALL_WAITPID: usize = 260;
+// Syscalls of this kernel alone take numbers from 1000, which Linux does not use,
+// so that they are never mistaken for the Linux ones.
+/// Like `dup3` of Linux without flags, but `old_fd` may equal `new_fd`.
+const SYSCALL_DUP2: usize = 1000;
+/// Sleeps for a period in milliseconds, unlike `nanosleep` of Linux.
+const SYSCALL_SLEEP: usize = 1001;
 
-pub fn syscall(syscall_id: usize, args: [usize; 3]) -> isize {
-    match syscall_id {
+/// Dispatches a syscall, and returns the result in the convention of `a0`,
+/// where errors are negative errno values.
+pub fn syscall(syscall_id: usize, args: [usize; 6]) -> isize {
+    let result = match syscall_id {
+        SYSCALL_DUP2 => sys_dup2(args[0], args[1]),
+        SYSCALL_DUP => sys_dup(args[0]),
+        SYSCALL_MKDIRAT => sys_mkdirat(args[0] as isize, args[1] as *const u8, args[2] as u32),
+        SYSCALL_UNLINKAT => sys_unlinkat(args[0] as isize, args[1] as *const u8, args[2] as u32),
+        SYSCALL_LINKAT => sys_linkat(
+            args[0] as isize,
+            args[1] as *const u8,
+            args[2] as isize,
+            args[3] as *const u8,
+            args[4] as u32
+        ),
+        SYSCALL_OPENAT => sys_openat(args[0] as isize, args[1] as *const u8, args[2] as u32),
+        SYSCALL_CLOSE => sys_close(args[0]),
+        SYSCALL_PIPE => sys_pipe(args[0] as *mut usize),
+        SYSCALL_GETDENTS64 => sys_getdents64(args[0], args[1] as *mut u8, args[2]),
+        SYSCALL_LSEEK => sys_lseek(args[0], args[1] as isize, args[2]),
+        SYSCALL_READ => sys_read(args[0], args[1] as *mut u8, args[2]),
         SYSCALL_WRITE => sys_write(args[0], args[1] as *const u8, args[2]),
+        SYSCALL_FSTAT => sys_fstat(args[0], args[1] as *mut Stat),
         SYSCALL_EXIT => sys_exit(args[0] as i32),
-        _ => panic!("Unsupported syscall_id: {}", syscall_id),
-    }
-}
+        SYSCALL_SLEEP => sys_sleep(args[0]),
+        SYSCALL_YIELD => sys_yield(),
+        SYSCALL_GET_TIME => sys_get_time(args[0] as *mut TimeVal, args[1]),
+        SYSCALL_GETPID => sys_getpid(),
+        SYSCALL_FORK => sys_fork(),
+        SYSCALL_EXEC => sys_exec(args[0] as *const u8, args[1] as *const usize),
+        SYSCALL_WAITPID => sys_waitpid(args[0] as isize, args[1] as *mut i32),
+        _ => {
+            warn!("[kernel] Unsupported syscall_id: {}", syscall_id);
+            Err(SysError::NoSys)
+        }
+    };
 
-#[allow(clippy::not_unsafe_ptr_arg_deref)]
-pub fn sys_write(fd: usize, buffer: *const u8, length: usize) -> isize {
-    match fd {
-        STDOUT => {
-            let slice = unsafe { core::slice::from_raw_parts(buffer, length) };
-            let str = core::str::from_utf8(slice).unwrap();
-            print!("{}", str);
-
-            length as isize
-        },
-        _ => unimplemented!("Unsupported file direction: {}.", fd)
+    match result {
+        Ok(ret) => ret as isize,
+        Err(err) => -err.errno()
     }
 }
-
-pub fn sys_exit(code: i32) -> ! {
-    info!("[kernel] Application exited with code {}", code);
-    
-    run_next_app();
-}
\ No newline at end of file
@@ -0,0 +1,70 @@
+use easy_fs::FsError;
+
+/// Errors of syscalls, whose discriminants are the Linux errno values.
+/// They are returned to user space negated in `a0`.
+#[repr(isize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SysError {
+    /// `EPERM`: the operation is not permitted, e.g. linking a directory.
+    Perm = 1,
+    /// `ENOENT`: no such file or app.
+    NoEnt = 2,
+    /// `E2BIG`: the arguments of `exec` are too many or too long.
+    TooBig = 7,
+    /// `ENOEXEC`: the file is not a valid executable.
+    NoExec = 8,
+    /// `EBADF`: bad file descriptor, or one opened without the required access.
+    BadF = 9,
+    /// `ECHILD`: no child matches.
+    Child = 10,
+    /// `EAGAIN`: the resource is temporarily unavailable, and the call should be retried.
+    Again = 11,
+    /// `EFAULT`: the user pointer is not accessible by the caller.
+    Fault = 14,
+    /// `EEXIST`: the path exists already.
+    Exist = 17,
+    /// `ENOTDIR`: a component of the path is not a directory.
+    NotDir = 20,
+    /// `EISDIR`: the path is a directory, where a file is required.
+    IsDir = 21,
+    /// `EINVAL`: invalid argument.
+    Inval = 22,
+    /// `EMFILE`: the process has too many open files.
+    MFile = 24,
+    /// `ENOSPC`: no space left on the device.
+    NoSpc = 28,
+    /// `ESPIPE`: the file cannot be seeked, e.g. a pipe or the console.
+    SPipe = 29,
+    /// `EPIPE`: the pipe has no read end left.
+    Pipe = 32,
+    /// `ENAMETOOLONG`: a name in the path is too long.
+    NameTooLong = 36,
+    /// `ENOSYS`: the syscall is not implemented.
+    NoSys = 38,
+    /// `ENOTEMPTY`: the directory to be removed is not empty.
+    NotEmpty = 39
+}
+
+pub type SysResult = Result<usize, SysError>;
+
+impl SysError {
+    pub fn errno(self) -> isize {
+        self as isize
+    }
+}
+
+impl From<FsError> for SysError {
+    fn from(err: FsError) -> Self {
+        match err {
+            FsError::NotFound => Self::NoEnt,
+            FsError::AlreadyExists => Self::Exist,
+            FsError::NotDir => Self::NotDir,
+            FsError::IsDir => Self::IsDir,
+            FsError::NotEmpty => Self::NotEmpty,
+            FsError::NameTooLong => Self::NameTooLong,
+            FsError::NoSpace => Self::NoSpc,
+            FsError::InvalidName => Self::Inval,
+            FsError::NotPermitted => Self::Perm
+        }
+    }
+}
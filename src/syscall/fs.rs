@@ -0,0 +1,331 @@
+use super::{SysError, SysResult};
+use alloc::{string::String, vec};
+use core::slice;
+use crate::{
+    fs::{
+        MAX_DIRENT_LEN, OpenFlags, SeekFrom, Stat, StatMode,
+        link_file, make_dir, make_pipe, open_file, unlink_file
+    },
+    mm::{PAGE_SIZE, UserBuffer, copy_to_user, translated_cstr},
+    task::{MAX_FD, current_process, current_user_token}
+};
+
+/// Size of the kernel buffer through which `read`, `write` and `getdents64` copy user data,
+/// so that a large user buffer is handled chunk by chunk.
+const BOUNCE_BUFFER_SIZE: usize = PAGE_SIZE;
+/// Directory fd of `openat` standing for the current working directory.
+const AT_FDCWD: isize = -100;
+/// Flag of `unlinkat` to remove a directory rather than a file.
+const AT_REMOVEDIR: u32 = 0x200;
+const SEEK_SET: usize = 0;
+const SEEK_CUR: usize = 1;
+const SEEK_END: usize = 2;
+
+/// Copies a NUL-terminated string from the user space with given token.
+/// Fails with `EFAULT` if it is not readable or too long, or `EINVAL` if it is not UTF-8.
+pub(super) fn user_str(token: usize, ptr: *const u8) -> Result<String, SysError> {
+    let bytes = translated_cstr(token, ptr).ok_or(SysError::Fault)?;
+
+    String::from_utf8(bytes).map_err(|_| SysError::Inval)
+}
+
+/// Writes `buffer` to the file, which may block until all bytes are taken.
+/// Returns the number of bytes written. Fails with `EPIPE` on a pipe with no read end left.
+pub fn sys_write(fd: usize, buffer: *const u8, length: usize) -> SysResult {
+    let process = current_process().expect("No process is running.");
+    let inner = process.inner_exclusive_access();
+    let Some(Some(file)) = inner.fd_table.get(fd) else {
+        return Err(SysError::BadF);
+    };
+    if !file.writable() {
+        return Err(SysError::BadF);
+    }
+
+    // The borrow must be released, as writing to the file may block.
+    let file = file.clone();
+    drop(inner);
+
+    // Any bytes are accepted, as files are written byte by byte.
+    let token = current_user_token();
+    let mut bounce = vec![0u8; length.min(BOUNCE_BUFFER_SIZE)];
+    let mut written = 0;
+    while written < length {
+        let len = (length - written).min(BOUNCE_BUFFER_SIZE);
+        let Some(user_buffer) = UserBuffer::new(token, buffer.wrapping_add(written), len, false) else {
+            // Bytes already written cannot be taken back, so they are reported instead.
+            return if written == 0 { Err(SysError::Fault) } else { Ok(written) };
+        };
+        user_buffer.read(&mut bounce[..len]);
+        // A broken pipe is only reported if nothing has been written.
+        let Some(count) = file.write(&bounce[..len]) else {
+            return if written == 0 { Err(SysError::Pipe) } else { Ok(written) };
+        };
+        written += count;
+        if count < len {
+            break;
+        }
+    }
+
+    Ok(written)
+}
+
+/// Reads into `buffer` from the file, which may block until some bytes are available.
+/// Returns the number of bytes read. Fails with `EISDIR` on a directory.
+pub fn sys_read(fd: usize, buffer: *mut u8, length: usize) -> SysResult {
+    let process = current_process().expect("No process is running.");
+    let inner = process.inner_exclusive_access();
+    let Some(Some(file)) = inner.fd_table.get(fd) else {
+        return Err(SysError::BadF);
+    };
+    if !file.readable() {
+        return Err(SysError::BadF);
+    }
+    // Entries of a directory are read by `getdents64` instead.
+    if file.stat().is_some_and(|stat| stat.mode.contains(StatMode::DIR)) {
+        return Err(SysError::IsDir);
+    }
+
+    // The borrow must be released, as reading from the file may block.
+    let file = file.clone();
+    drop(inner);
+
+    let token = current_user_token();
+    let mut bounce = vec![0u8; length.min(BOUNCE_BUFFER_SIZE)];
+    let mut read = 0;
+    while read < length {
+        let len = (length - read).min(BOUNCE_BUFFER_SIZE);
+        // Each chunk is validated before reading, so that no byte is consumed in vain.
+        let Some(mut user_buffer) = UserBuffer::new(token, buffer.wrapping_add(read), len, true) else {
+            return if read == 0 { Err(SysError::Fault) } else { Ok(read) };
+        };
+        let count = file.read(&mut bounce[..len]);
+        user_buffer.fill(&bounce[..count]);
+        read += count;
+        // A short read means no more bytes are available for now.
+        if count < len {
+            break;
+        }
+    }
+
+    Ok(read)
+}
+
+/// Checks the directory fd of the `*at` syscalls. Paths are always resolved
+/// from the root directory, which is the working directory of every process,
+/// so it must be `AT_FDCWD`.
+fn check_dirfd(dirfd: isize) -> Result<(), SysError> {
+    if dirfd == AT_FDCWD {
+        Ok(())
+    } else {
+        Err(SysError::BadF)
+    }
+}
+
+/// Opens the file at `path` with `flags`, and returns its descriptor.
+/// Fails with `EMFILE` if the process has no free descriptor.
+pub fn sys_openat(dirfd: isize, path: *const u8, flags: u32) -> SysResult {
+    check_dirfd(dirfd)?;
+
+    let flags = OpenFlags::from_bits_truncate(flags);
+    if flags.read_write().is_none() {
+        return Err(SysError::Inval);
+    }
+    let path = user_str(current_user_token(), path)?;
+
+    let process = current_process().expect("No process is running.");
+    let mut inner = process.inner_exclusive_access();
+    // The descriptor is taken before opening, so that no file is created or truncated
+    // without one. Its slot stays free if opening fails.
+    let fd = inner.alloc_fd().ok_or(SysError::MFile)?;
+    inner.fd_table[fd] = Some(open_file(&path, flags)?);
+
+    Ok(fd)
+}
+
+pub fn sys_close(fd: usize) -> SysResult {
+    let process = current_process().expect("No process is running.");
+    let mut inner = process.inner_exclusive_access();
+    // The file is closed once its last descriptor is dropped.
+    inner.fd_table
+        .get_mut(fd)
+        .and_then(|file| file.take())
+        .ok_or(SysError::BadF)?;
+
+    Ok(0)
+}
+
+/// Moves the offset of the file by `offset` from the position given by `whence`,
+/// and returns the new offset. Fails with `ESPIPE` on pipes and the console,
+/// or `EINVAL` if the new offset would be negative.
+pub fn sys_lseek(fd: usize, offset: isize, whence: usize) -> SysResult {
+    let pos = match whence {
+        SEEK_SET => SeekFrom::Start(usize::try_from(offset).map_err(|_| SysError::Inval)?),
+        SEEK_CUR => SeekFrom::Current(offset),
+        SEEK_END => SeekFrom::End(offset),
+        _ => return Err(SysError::Inval)
+    };
+
+    let process = current_process().expect("No process is running.");
+    let inner = process.inner_exclusive_access();
+    let Some(Some(file)) = inner.fd_table.get(fd) else {
+        return Err(SysError::BadF);
+    };
+    // Only files on the filesystem have an offset.
+    if file.stat().is_none() {
+        return Err(SysError::SPipe);
+    }
+
+    file.seek(pos).ok_or(SysError::Inval)
+}
+
+/// Stores the status of the file into `stat`. Fails with `EINVAL` on pipes and the console.
+pub fn sys_fstat(fd: usize, stat: *mut Stat) -> SysResult {
+    let process = current_process().expect("No process is running.");
+    let inner = process.inner_exclusive_access();
+    let Some(Some(file)) = inner.fd_table.get(fd) else {
+        return Err(SysError::BadF);
+    };
+
+    let file_stat = file.stat().ok_or(SysError::Inval)?;
+    let bytes = unsafe {
+        slice::from_raw_parts(&file_stat as *const Stat as *const u8, size_of::<Stat>())
+    };
+    copy_to_user(inner.get_user_token(), stat as *mut u8, bytes).ok_or(SysError::Fault)?;
+
+    Ok(0)
+}
+
+/// Creates a directory at `path`. There are no permissions, so `mode` is ignored.
+pub fn sys_mkdirat(dirfd: isize, path: *const u8, _mode: u32) -> SysResult {
+    check_dirfd(dirfd)?;
+
+    let path = user_str(current_user_token(), path)?;
+    make_dir(&path)?;
+
+    Ok(0)
+}
+
+/// Adds a hard link at `new_path` to the file at `old_path`. No `flags` are supported.
+pub fn sys_linkat(
+    old_dirfd: isize,
+    old_path: *const u8,
+    new_dirfd: isize,
+    new_path: *const u8,
+    flags: u32
+) -> SysResult {
+    check_dirfd(old_dirfd)?;
+    check_dirfd(new_dirfd)?;
+    if flags != 0 {
+        return Err(SysError::Inval);
+    }
+
+    let token = current_user_token();
+    let old_path = user_str(token, old_path)?;
+    let new_path = user_str(token, new_path)?;
+    link_file(&old_path, &new_path)?;
+
+    Ok(0)
+}
+
+/// Removes the entry at `path`, which is an empty directory if `AT_REMOVEDIR` is in `flags`,
+/// or a file otherwise.
+pub fn sys_unlinkat(dirfd: isize, path: *const u8, flags: u32) -> SysResult {
+    check_dirfd(dirfd)?;
+    if flags & !AT_REMOVEDIR != 0 {
+        return Err(SysError::Inval);
+    }
+
+    let path = user_str(current_user_token(), path)?;
+    unlink_file(&path, flags & AT_REMOVEDIR != 0)?;
+
+    Ok(0)
+}
+
+/// Reads entries of the directory into `buffer` as `linux_dirent64` records, and returns
+/// the number of bytes read, which is 0 at the end. The buffer must fit any single record.
+pub fn sys_getdents64(fd: usize, buffer: *mut u8, length: usize) -> SysResult {
+    if length < MAX_DIRENT_LEN {
+        return Err(SysError::Inval);
+    }
+
+    let process = current_process().expect("No process is running.");
+    let inner = process.inner_exclusive_access();
+    let Some(Some(file)) = inner.fd_table.get(fd) else {
+        return Err(SysError::BadF);
+    };
+    let file = file.clone();
+    drop(inner);
+
+    // Entries which do not fit in one chunk are left for the next call.
+    let length = length.min(BOUNCE_BUFFER_SIZE);
+    let mut user_buffer = UserBuffer::new(current_user_token(), buffer, length, true)
+        .ok_or(SysError::Fault)?;
+    let mut data = vec![0u8; length];
+    let count = file.read_dir(&mut data).ok_or(SysError::NotDir)?;
+    user_buffer.fill(&data[..count]);
+
+    Ok(count)
+}
+
+/// Creates a pipe, and stores the descriptors of its read end and write end into `pipe`.
+/// Fails with `EMFILE` if the process has no two free descriptors.
+pub fn sys_pipe(pipe: *mut usize) -> SysResult {
+    let process = current_process().expect("No process is running.");
+    let mut inner = process.inner_exclusive_access();
+    let (read_end, write_end) = make_pipe();
+
+    let read_fd = inner.alloc_fd().ok_or(SysError::MFile)?;
+    inner.fd_table[read_fd] = Some(read_end);
+    let Some(write_fd) = inner.alloc_fd() else {
+        inner.fd_table[read_fd] = None;
+        return Err(SysError::MFile);
+    };
+    inner.fd_table[write_fd] = Some(write_end);
+
+    let fds = [read_fd, write_fd];
+    let bytes = unsafe { slice::from_raw_parts(fds.as_ptr() as *const u8, size_of_val(&fds)) };
+    if copy_to_user(inner.get_user_token(), pipe as *mut u8, bytes).is_none() {
+        inner.fd_table[read_fd] = None;
+        inner.fd_table[write_fd] = None;
+        return Err(SysError::Fault);
+    }
+
+    Ok(0)
+}
+
+/// Duplicates `fd` to the lowest free descriptor, which is returned.
+/// Fails with `EMFILE` if the process has no free descriptor.
+pub fn sys_dup(fd: usize) -> SysResult {
+    let process = current_process().expect("No process is running.");
+    let mut inner = process.inner_exclusive_access();
+    let file = inner.fd_table
+        .get(fd)
+        .and_then(|file| file.clone())
+        .ok_or(SysError::BadF)?;
+
+    let new_fd = inner.alloc_fd().ok_or(SysError::MFile)?;
+    inner.fd_table[new_fd] = Some(file);
+
+    Ok(new_fd)
+}
+
+/// Duplicates `old_fd` to `new_fd`, which is closed first if it is open. Returns `new_fd`.
+pub fn sys_dup2(old_fd: usize, new_fd: usize) -> SysResult {
+    if new_fd >= MAX_FD {
+        return Err(SysError::BadF);
+    }
+
+    let process = current_process().expect("No process is running.");
+    let mut inner = process.inner_exclusive_access();
+    let file = inner.fd_table
+        .get(old_fd)
+        .and_then(|file| file.clone())
+        .ok_or(SysError::BadF)?;
+
+    if new_fd >= inner.fd_table.len() {
+        inner.fd_table.resize(new_fd + 1, None);
+    }
+    inner.fd_table[new_fd] = Some(file);
+
+    Ok(new_fd)
+}
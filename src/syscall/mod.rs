@@ -1,8 +1,11 @@
-
 // Include section.
 mod call;
+mod error;
+mod fs;
 mod info;
+mod process;
 
 // Export section.
 pub use call::*;
+pub use error::{SysError, SysResult};
 pub use info::*;
@@ -0,0 +1,136 @@
+use super::{SysError, SysResult, fs::user_str};
+use alloc::{string::String, sync::Arc, vec::Vec};
+use core::slice;
+use crate::{
+    info,
+    fs::read_app,
+    mm::{copy_to_user, translated_ref, translated_refmut},
+    task::{
+        ProcessControlBlock, block_current_and_run_next, exit_current_and_run_next,
+        suspend_current_and_run_next, add_process, current_app_name, current_process, current_user_token
+    },
+    timer::{TimeVal, add_sleeper, get_time_ms, get_time_us}
+};
+
+/// Most arguments accepted by `exec`.
+const MAX_ARGS: usize = 32;
+/// Most bytes taken by the arguments of `exec` in the user stack, including `argv` itself.
+const MAX_ARGS_SIZE: usize = 2048;
+
+pub fn sys_exit(code: i32) -> ! {
+    info!("[kernel] Application {} exited with code {}", current_app_name(), code);
+
+    exit_current_and_run_next(code);
+}
+
+pub fn sys_yield() -> SysResult {
+    suspend_current_and_run_next();
+
+    Ok(0)
+}
+
+/// Writes the time since boot into `ts`, while the timezone is ignored.
+pub fn sys_get_time(ts: *mut TimeVal, _tz: usize) -> SysResult {
+    let time = TimeVal::from_us(get_time_us());
+    let bytes = unsafe {
+        slice::from_raw_parts(&time as *const TimeVal as *const u8, size_of::<TimeVal>())
+    };
+    copy_to_user(current_user_token(), ts as *mut u8, bytes).ok_or(SysError::Fault)?;
+
+    Ok(0)
+}
+
+/// Blocks the current process for at least `period_ms` milliseconds, during which
+/// other processes run.
+pub fn sys_sleep(period_ms: usize) -> SysResult {
+    let expire_ms = get_time_ms().checked_add(period_ms).ok_or(SysError::Inval)?;
+    add_sleeper(expire_ms, current_process().expect("No process is running."));
+    block_current_and_run_next();
+
+    Ok(0)
+}
+
+pub fn sys_getpid() -> SysResult {
+    Ok(current_process().expect("No process is running.").getpid())
+}
+
+/// Returns the pid of the child in the parent, and 0 in the child.
+pub fn sys_fork() -> SysResult {
+    let current = current_process().expect("No process is running.");
+    let child = current.fork();
+    let child_pid = child.getpid();
+    // The child returns from the same `ecall`, but with 0 in `a0`.
+    child.inner_exclusive_access().get_trap_cx()[10] = 0;
+    add_process(child);
+
+    Ok(child_pid)
+}
+
+/// Runs the app in the file at `path` in the current process, with the arguments in
+/// the null-terminated array `args`, and returns `argc` which is passed in `a0`.
+/// Fails with `ENOENT` if there is no such file, `EISDIR` if it is a directory, `ENOEXEC`
+/// if it is not a valid ELF file, `E2BIG` if the arguments are too many or too long,
+/// or `EFAULT` if the path or arguments are not readable.
+pub fn sys_exec(path: *const u8, mut args: *const usize) -> SysResult {
+    let token = current_user_token();
+    let path = user_str(token, path)?;
+
+    let mut arg_vec: Vec<String> = Vec::new();
+    let mut args_size = 0;
+    // A null `args` is taken as an empty array.
+    while !args.is_null() {
+        let arg_ptr = *translated_ref(token, args).ok_or(SysError::Fault)?;
+        if arg_ptr == 0 {
+            break;
+        }
+
+        let arg = user_str(token, arg_ptr as *const u8)?;
+        args_size += arg.len() + 1 + size_of::<usize>();
+        if arg_vec.len() == MAX_ARGS || args_size > MAX_ARGS_SIZE {
+            return Err(SysError::TooBig);
+        }
+        arg_vec.push(arg);
+        args = args.wrapping_add(1);
+    }
+
+    let elf_data = read_app(&path)?;
+    let current = current_process().expect("No process is running.");
+    current.exec(&path, &elf_data, &arg_vec).ok_or(SysError::NoExec)?;
+
+    Ok(arg_vec.len())
+}
+
+/// Reaps a zombie child with given pid, or any child if `pid` is -1, and stores its exit code.
+/// Fails with `ECHILD` if there is no such child, or `EAGAIN` if it is still running.
+pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32) -> SysResult {
+    if pid < -1 {
+        return Err(SysError::Inval);
+    }
+
+    let process = current_process().expect("No process is running.");
+    let mut inner = process.inner_exclusive_access();
+    let matches = |child: &Arc<ProcessControlBlock>| pid == -1 || pid as usize == child.getpid();
+
+    if !inner.children.iter().any(matches) {
+        return Err(SysError::Child);
+    }
+
+    // A zombie still referenced elsewhere, e.g. queued by the scheduler, is left for a later call,
+    // so that it is released along with its last reference.
+    let Some(idx) = inner.children
+        .iter()
+        .position(|child| {
+            matches(child) && Arc::strong_count(child) == 1 && child.inner_exclusive_access().is_zombie()
+        })
+    else {
+        return Err(SysError::Again);
+    };
+
+    // The pointer is checked before reaping, so the child is kept if it is bad.
+    let exit_code = translated_refmut(inner.get_user_token(), exit_code_ptr).ok_or(SysError::Fault)?;
+    *exit_code = inner.children[idx].inner_exclusive_access().exit_code;
+
+    let child = inner.children.remove(idx);
+
+    Ok(child.getpid())
+}
@@ -0,0 +1,31 @@
+use crate::trap::trap_return;
+
+/// Kernel execution state of a task, which is saved and restored by `__switch`.
+/// Only `ra`, `sp` and callee-saved registers are required, as `__switch` is called like a function.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TaskContext {
+    ra: usize,
+    sp: usize,
+    s: [usize; 12]
+}
+
+impl TaskContext {
+    pub fn zero_init() -> Self {
+        Self {
+            ra: 0,
+            sp: 0,
+            s: [0usize; 12]
+        }
+    }
+
+    /// Creates a context which returns to user mode through `trap_return`,
+    /// with an empty kernel stack whose top is `kstack_ptr`.
+    pub fn goto_trap_return(kstack_ptr: usize) -> Self {
+        Self {
+            ra: trap_return as usize,
+            sp: kstack_ptr,
+            s: [0usize; 12]
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use super::ProcessControlBlock;
+use alloc::{collections::VecDeque, sync::Arc};
+use crate::sync::UPCell;
+use lazy_static::lazy_static;
+
+lazy_static! {
+    static ref PROCESS_MANAGER: UPCell<ProcessManager> = unsafe {
+        UPCell::new(ProcessManager::new())
+    };
+}
+
+/// Holds the ready processes, which are scheduled in a FIFO way.
+struct ProcessManager {
+    ready_queue: VecDeque<Arc<ProcessControlBlock>>
+}
+
+impl ProcessManager {
+    fn new() -> Self {
+        Self { ready_queue: VecDeque::new() }
+    }
+
+    fn add(&mut self, process: Arc<ProcessControlBlock>) {
+        self.ready_queue.push_back(process);
+    }
+
+    fn fetch(&mut self) -> Option<Arc<ProcessControlBlock>> {
+        self.ready_queue.pop_front()
+    }
+}
+
+pub fn add_process(process: Arc<ProcessControlBlock>) {
+    PROCESS_MANAGER.borrow_mut().add(process);
+}
+
+pub fn fetch_process() -> Option<Arc<ProcessControlBlock>> {
+    PROCESS_MANAGER.borrow_mut().fetch()
+}
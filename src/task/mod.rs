@@ -0,0 +1,120 @@
+use crate::{
+    info, shutdown,
+    fs::read_app
+};
+use alloc::{string::String, sync::Arc};
+use core::arch::global_asm;
+use lazy_static::lazy_static;
+
+// Include section.
+mod ctx;
+mod manager;
+mod pcb;
+mod pid;
+mod processor;
+
+// Export section.
+pub use ctx::TaskContext;
+pub use manager::{add_process, fetch_process};
+pub use pcb::{MAX_FD, ProcessControlBlock, ProcessStatus};
+pub use pid::{KernelStack, PidHandle, pid_alloc};
+pub use processor::{run_processes, schedule, current_process, current_user_token, current_trap_cx};
+
+global_asm!(include_str!("switch.S"));
+
+unsafe extern "C" {
+    /// Saves the kernel execution state of current task into `current_task_cx_ptr`,
+    /// and resumes the one stored in `next_task_cx_ptr`.
+    fn __switch(current_task_cx_ptr: *mut TaskContext, next_task_cx_ptr: *const TaskContext);
+}
+
+lazy_static! {
+    /// The first process, which spawns the shell and adopts all orphans.
+    pub static ref INITPROC: Arc<ProcessControlBlock> = {
+        let elf_data = read_app("initproc").expect("App initproc is not found.");
+        let process = ProcessControlBlock::new("initproc", &elf_data).expect("Invalid ELF file of initproc.");
+
+        Arc::new(process)
+    };
+}
+
+/// Adds the init process, from which every other process is forked.
+pub fn init() {
+    info!("[kernel] Loading initproc...");
+    add_process(INITPROC.clone());
+}
+
+/// Gives up the CPU, and the current process will be scheduled again later.
+pub fn suspend_current_and_run_next() {
+    let process = current_process().expect("No process is running.");
+    let mut inner = process.inner_exclusive_access();
+    inner.status = ProcessStatus::Ready;
+    let task_cx_ptr = &mut inner.task_cx as *mut TaskContext;
+    drop(inner);
+    // The idle control flow puts the process back to the ready queue.
+    drop(process);
+
+    schedule(task_cx_ptr);
+}
+
+/// Gives up the CPU until the current process is woken up by [`wakeup_process`].
+pub fn block_current_and_run_next() {
+    let process = current_process().expect("No process is running.");
+    let mut inner = process.inner_exclusive_access();
+    inner.status = ProcessStatus::Blocked;
+    let task_cx_ptr = &mut inner.task_cx as *mut TaskContext;
+    drop(inner);
+    drop(process);
+
+    schedule(task_cx_ptr);
+}
+
+/// Puts a blocked process back to the ready queue.
+pub fn wakeup_process(process: Arc<ProcessControlBlock>) {
+    process.inner_exclusive_access().status = ProcessStatus::Ready;
+    add_process(process);
+}
+
+/// Turns the current process into a zombie with `exit_code`, which waits to be reaped by its parent.
+pub fn exit_current_and_run_next(exit_code: i32) -> ! {
+    let process = current_process().expect("No process is running.");
+    if Arc::ptr_eq(&process, &INITPROC) {
+        info!("[kernel] Initproc exited with code {}.", exit_code);
+        shutdown!(exit_code != 0);
+    }
+
+    let mut inner = process.inner_exclusive_access();
+    inner.status = ProcessStatus::Zombie;
+    inner.exit_code = exit_code;
+
+    // Orphans are adopted by the init process, which reaps them later.
+    {
+        let mut initproc_inner = INITPROC.inner_exclusive_access();
+        for child in inner.children.iter() {
+            child.inner_exclusive_access().parent = Some(Arc::downgrade(&INITPROC));
+            initproc_inner.children.push(child.clone());
+        }
+    }
+    inner.children.clear();
+
+    // Files are closed now, so that pipe readers see EOF without waiting for the reaper.
+    inner.fd_table.clear();
+
+    // The kernel space is active now, so the user memory can be recycled in advance.
+    inner.memory_set.recycle_data_pages();
+    drop(inner);
+    drop(process);
+
+    let mut unused = TaskContext::zero_init();
+    schedule(&mut unused as *mut TaskContext);
+
+    unreachable!("Unreachable code after a zombie is switched out.");
+}
+
+pub fn current_app_name() -> String {
+    current_process()
+        .expect("No process is running.")
+        .inner_exclusive_access()
+        .name
+        .clone()
+}
@@ -0,0 +1,216 @@
+use super::{TaskContext, KernelStack, PidHandle, pid_alloc};
+use alloc::{
+    string::String,
+    vec,
+    sync::{Arc, Weak},
+    vec::Vec
+};
+use core::{cell::RefMut, slice};
+use crate::{
+    fs::{File, Stdin, Stdout, Stderr},
+    mm::{KERNEL_SPACE, MemorySet, copy_to_user, PhysPageNum, VirtAddr, TRAP_CONTEXT},
+    sync::UPCell,
+    trap::{TrapContext, trap_handler}
+};
+
+/// Upper bound of file descriptors, which keeps the table of a process from growing without limit.
+pub const MAX_FD: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessStatus {
+    Ready,
+    Running,
+    /// The process waits for an event like a timer, and it is out of the ready queue meanwhile.
+    Blocked,
+    /// The process has exited, but it is not reaped by its parent yet.
+    Zombie
+}
+
+pub struct ProcessControlBlock {
+    pub pid: PidHandle,
+    /// Kernel stack of the process, which `task_cx` and the trap context point into.
+    kernel_stack: KernelStack,
+    inner: UPCell<ProcessControlBlockInner>
+}
+
+/// Mutable part of a process, which is accessed through [`ProcessControlBlock::inner_exclusive_access`].
+pub struct ProcessControlBlockInner {
+    /// Name of the app running in the process.
+    pub name: String,
+    pub status: ProcessStatus,
+    pub task_cx: TaskContext,
+    pub memory_set: MemorySet,
+    /// Frame of the trap context, which is accessed by the kernel directly.
+    pub trap_cx_ppn: PhysPageNum,
+    pub parent: Option<Weak<ProcessControlBlock>>,
+    pub children: Vec<Arc<ProcessControlBlock>>,
+    pub exit_code: i32,
+    /// Open files indexed by file descriptors, where `None` marks a free slot.
+    pub fd_table: Vec<Option<Arc<dyn File>>>
+}
+
+impl ProcessControlBlockInner {
+    pub fn get_trap_cx(&self) -> &'static mut TrapContext {
+        self.trap_cx_ppn.get_mut()
+    }
+
+    pub fn get_user_token(&self) -> usize {
+        self.memory_set.token()
+    }
+
+    pub fn is_zombie(&self) -> bool {
+        self.status == ProcessStatus::Zombie
+    }
+
+    /// Returns the lowest free file descriptor, and extends the table if there is none.
+    /// Returns `None` if all [`MAX_FD`] descriptors are in use.
+    pub fn alloc_fd(&mut self) -> Option<usize> {
+        if let Some(fd) = self.fd_table.iter().position(|file| file.is_none()) {
+            Some(fd)
+        } else if self.fd_table.len() < MAX_FD {
+            self.fd_table.push(None);
+            Some(self.fd_table.len() - 1)
+        } else {
+            None
+        }
+    }
+}
+
+impl ProcessControlBlock {
+    /// Creates a process from the ELF file of an app, or returns `None` if it cannot be loaded.
+    pub fn new(name: &str, elf_data: &[u8]) -> Option<Self> {
+        let (memory_set, user_sp, entry) = MemorySet::from_elf(elf_data)?;
+        let trap_cx_ppn = memory_set
+            .translate(VirtAddr::from(TRAP_CONTEXT).into())
+            .unwrap()
+            .ppn();
+        let pid = pid_alloc();
+        let kernel_stack = KernelStack::new(&pid);
+        let kernel_sp = kernel_stack.get_top();
+
+        *trap_cx_ppn.get_mut() = TrapContext::new(
+            entry,
+            user_sp,
+            KERNEL_SPACE.borrow_mut().token(),
+            kernel_sp,
+            trap_handler as usize
+        );
+
+        let inner = ProcessControlBlockInner {
+            name: name.into(),
+            status: ProcessStatus::Ready,
+            task_cx: TaskContext::goto_trap_return(kernel_sp),
+            memory_set,
+            trap_cx_ppn,
+            parent: None,
+            children: Vec::new(),
+            exit_code: 0,
+            fd_table: vec![
+                Some(Arc::new(Stdin)),
+                Some(Arc::new(Stdout)),
+                Some(Arc::new(Stderr))
+            ]
+        };
+
+        Some(Self {
+            pid,
+            kernel_stack,
+            inner: unsafe { UPCell::new(inner) }
+        })
+    }
+
+    pub fn inner_exclusive_access(&self) -> RefMut<'_, ProcessControlBlockInner> {
+        self.inner.borrow_mut()
+    }
+
+    pub fn getpid(&self) -> usize {
+        self.pid.0
+    }
+
+    /// Creates a child process with a copy of the address space, which resumes from the same trap.
+    pub fn fork(self: &Arc<Self>) -> Arc<Self> {
+        let mut parent_inner = self.inner_exclusive_access();
+        let memory_set = MemorySet::from_existed_user(&parent_inner.memory_set);
+        let trap_cx_ppn = memory_set
+            .translate(VirtAddr::from(TRAP_CONTEXT).into())
+            .unwrap()
+            .ppn();
+        let pid = pid_alloc();
+        let kernel_stack = KernelStack::new(&pid);
+        let kernel_sp = kernel_stack.get_top();
+
+        // The trap context is copied along with the space, except that the kernel stack differs.
+        let trap_cx: &mut TrapContext = trap_cx_ppn.get_mut();
+        trap_cx.kernel_sp = kernel_sp;
+
+        let inner = ProcessControlBlockInner {
+            name: parent_inner.name.clone(),
+            status: ProcessStatus::Ready,
+            task_cx: TaskContext::goto_trap_return(kernel_sp),
+            memory_set,
+            trap_cx_ppn,
+            parent: Some(Arc::downgrade(self)),
+            children: Vec::new(),
+            exit_code: 0,
+            // Open files are shared with the parent.
+            fd_table: parent_inner.fd_table.clone()
+        };
+        let child = Arc::new(Self {
+            pid,
+            kernel_stack,
+            inner: unsafe { UPCell::new(inner) }
+        });
+        parent_inner.children.push(child.clone());
+
+        child
+    }
+
+    /// Replaces the address space with the one of another app, and restarts from its entry
+    /// with `argc` in `a0` and `argv` in `a1`. Returns `None` and leaves the process untouched
+    /// if the ELF file cannot be loaded, or the arguments do not fit in the user stack.
+    pub fn exec(&self, name: &str, elf_data: &[u8], args: &[String]) -> Option<()> {
+        let (memory_set, mut user_sp, entry) = MemorySet::from_elf(elf_data)?;
+        let trap_cx_ppn = memory_set
+            .translate(VirtAddr::from(TRAP_CONTEXT).into())
+            .unwrap()
+            .ppn();
+
+        // The null-terminated `argv` is pushed onto the new user stack first,
+        // and the strings it points to are pushed below it.
+        let token = memory_set.token();
+        user_sp -= (args.len() + 1) * size_of::<usize>();
+        let argv_base = user_sp;
+        let mut argv = Vec::with_capacity(args.len() + 1);
+        for arg in args.iter() {
+            user_sp -= arg.len() + 1;
+            copy_to_user(token, user_sp as *mut u8, arg.as_bytes())?;
+            copy_to_user(token, (user_sp + arg.len()) as *mut u8, &[0])?;
+            argv.push(user_sp);
+        }
+        argv.push(0);
+        let argv_bytes = unsafe {
+            slice::from_raw_parts(argv.as_ptr() as *const u8, argv.len() * size_of::<usize>())
+        };
+        copy_to_user(token, argv_base as *mut u8, argv_bytes)?;
+        // The stack pointer is kept 16-byte aligned as the calling convention requires.
+        user_sp &= !0xf;
+
+        let mut inner = self.inner_exclusive_access();
+        inner.name = name.into();
+        // The old space is dropped here, which recycles all its frames.
+        inner.memory_set = memory_set;
+        inner.trap_cx_ppn = trap_cx_ppn;
+        let trap_cx = inner.get_trap_cx();
+        *trap_cx = TrapContext::new(
+            entry,
+            user_sp,
+            KERNEL_SPACE.borrow_mut().token(),
+            self.kernel_stack.get_top(),
+            trap_handler as usize
+        );
+        trap_cx[10] = args.len();
+        trap_cx[11] = argv_base;
+
+        Some(())
+    }
+}
@@ -0,0 +1,103 @@
+use alloc::vec::Vec;
+use crate::{
+    mm::{KERNEL_SPACE, MapPermission, VirtAddr, PAGE_SIZE, TRAMPOLINE},
+    sync::UPCell
+};
+
+const KERNEL_STACK_SIZE: usize = 4096 * 2;
+
+static PID_ALLOCATOR: UPCell<PidAllocator> = unsafe {
+    UPCell::new(PidAllocator::new())
+};
+
+/// Hands out pids increasingly, and prefers the recycled ones, which are kept in a stack.
+pub struct PidAllocator {
+    current: usize,
+    recycled: Vec<usize>
+}
+
+impl PidAllocator {
+    pub const fn new() -> Self {
+        Self {
+            current: 0,
+            recycled: Vec::new()
+        }
+    }
+
+    pub fn alloc(&mut self) -> PidHandle {
+        if let Some(pid) = self.recycled.pop() {
+            PidHandle(pid)
+        } else {
+            self.current += 1;
+            PidHandle(self.current - 1)
+        }
+    }
+
+    pub fn dealloc(&mut self, pid: usize) {
+        if pid >= self.current || self.recycled.contains(&pid) {
+            panic!("Pid {} has not been allocated.", pid);
+        }
+
+        self.recycled.push(pid);
+    }
+}
+
+impl Default for PidAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An RAII handle of an allocated pid, which is returned to the allocator on drop.
+pub struct PidHandle(pub usize);
+
+impl Drop for PidHandle {
+    fn drop(&mut self) {
+        PID_ALLOCATOR.borrow_mut().dealloc(self.0);
+    }
+}
+
+pub fn pid_alloc() -> PidHandle {
+    PID_ALLOCATOR.borrow_mut().alloc()
+}
+
+/// Returns the bottom and top of the kernel stack of given pid. Stacks are placed
+/// downwards from [`TRAMPOLINE`] in kernel space, with an unmapped guard page below each,
+/// so that an overflow faults instead of corrupting the neighbor.
+pub fn kernel_stack_position(pid: usize) -> (usize, usize) {
+    let top = TRAMPOLINE - pid * (KERNEL_STACK_SIZE + PAGE_SIZE);
+    let bottom = top - KERNEL_STACK_SIZE;
+
+    (bottom, top)
+}
+
+/// The kernel stack of a process, which is mapped in kernel space until dropped.
+pub struct KernelStack {
+    pid: usize
+}
+
+impl KernelStack {
+    pub fn new(pid_handle: &PidHandle) -> Self {
+        let pid = pid_handle.0;
+        let (bottom, top) = kernel_stack_position(pid);
+        KERNEL_SPACE.borrow_mut().insert_framed_area(
+            bottom.into(),
+            top.into(),
+            MapPermission::R | MapPermission::W
+        );
+
+        Self { pid }
+    }
+
+    pub fn get_top(&self) -> usize {
+        kernel_stack_position(self.pid).1
+    }
+}
+
+impl Drop for KernelStack {
+    fn drop(&mut self) {
+        let (bottom, _) = kernel_stack_position(self.pid);
+        let bottom_va: VirtAddr = bottom.into();
+        KERNEL_SPACE.borrow_mut().remove_area_with_start_vpn(bottom_va.into());
+    }
+}
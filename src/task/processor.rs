@@ -0,0 +1,101 @@
+use super::{ProcessControlBlock, ProcessStatus, TaskContext, __switch, add_process, fetch_process};
+use alloc::sync::Arc;
+use crate::{
+    info, shutdown,
+    sync::UPCell,
+    timer::{has_sleepers, wake_sleepers},
+    trap::TrapContext
+};
+use lazy_static::lazy_static;
+
+lazy_static! {
+    static ref PROCESSOR: UPCell<Processor> = unsafe { UPCell::new(Processor::new()) };
+}
+
+/// State of the only CPU, which tracks the running process.
+struct Processor {
+    current: Option<Arc<ProcessControlBlock>>,
+    /// Context of the idle control flow in [`run_processes`], which runs on the boot stack.
+    idle_task_cx: TaskContext
+}
+
+impl Processor {
+    fn new() -> Self {
+        Self {
+            current: None,
+            idle_task_cx: TaskContext::zero_init()
+        }
+    }
+
+    fn get_idle_task_cx_ptr(&mut self) -> *mut TaskContext {
+        &mut self.idle_task_cx as *mut TaskContext
+    }
+
+    fn take_current(&mut self) -> Option<Arc<ProcessControlBlock>> {
+        self.current.take()
+    }
+
+    fn current(&self) -> Option<Arc<ProcessControlBlock>> {
+        self.current.clone()
+    }
+}
+
+/// Keeps switching to ready processes in the idle control flow, and shuts down once there is none
+/// and no process is sleeping.
+pub fn run_processes() -> ! {
+    loop {
+        let Some(process) = fetch_process() else {
+            // Timer interrupts are off in the kernel, so sleepers are polled while idle.
+            if has_sleepers() {
+                wake_sleepers();
+                continue;
+            }
+            info!("[kernel] All applications completed.");
+            shutdown!(false);
+        };
+
+        let mut processor = PROCESSOR.borrow_mut();
+        let idle_task_cx_ptr = processor.get_idle_task_cx_ptr();
+        let next_task_cx_ptr = {
+            let mut inner = process.inner_exclusive_access();
+            inner.status = ProcessStatus::Running;
+            &inner.task_cx as *const TaskContext
+        };
+        processor.current = Some(process);
+        // The borrow must be released, as the next process may borrow the processor again.
+        drop(processor);
+
+        unsafe { __switch(idle_task_cx_ptr, next_task_cx_ptr) }
+
+        // The process switched out is settled here, since its kernel stack is no longer in use.
+        // A zombie is dropped unless its parent still holds it.
+        let process = PROCESSOR.borrow_mut().take_current().unwrap();
+        if process.inner_exclusive_access().status == ProcessStatus::Ready {
+            add_process(process);
+        }
+    }
+}
+
+/// Switches from the current process saved in `switched_task_cx_ptr` to the idle control flow.
+pub fn schedule(switched_task_cx_ptr: *mut TaskContext) {
+    let idle_task_cx_ptr = PROCESSOR.borrow_mut().get_idle_task_cx_ptr();
+    unsafe { __switch(switched_task_cx_ptr, idle_task_cx_ptr) }
+}
+
+pub fn current_process() -> Option<Arc<ProcessControlBlock>> {
+    PROCESSOR.borrow_mut().current()
+}
+
+pub fn current_user_token() -> usize {
+    current_process()
+        .expect("No process is running.")
+        .inner_exclusive_access()
+        .get_user_token()
+}
+
+pub fn current_trap_cx() -> &'static mut TrapContext {
+    current_process()
+        .expect("No process is running.")
+        .inner_exclusive_access()
+        .get_trap_cx()
+}
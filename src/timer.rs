@@ -0,0 +1,104 @@
+use alloc::{collections::BinaryHeap, sync::Arc};
+use core::cmp::Ordering;
+use crate::{
+    sbi::set_timer,
+    sync::UPCell,
+    task::{ProcessControlBlock, wakeup_process}
+};
+use lazy_static::lazy_static;
+use riscv::register::time;
+
+/// Frequency of `time` CSR on qemu `virt` machine, in Hz.
+pub const CLOCK_FREQ: usize = 10_000_000;
+/// Length of a time slice, after which the running task is preempted.
+pub const TIME_SLICE_MS: usize = 10;
+
+const MSEC_PER_SEC: usize = 1000;
+const USEC_PER_SEC: usize = 1_000_000;
+
+/// Time since boot, which shares the layout with `struct timeval` in C.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TimeVal {
+    pub sec: usize,
+    pub usec: usize
+}
+
+impl TimeVal {
+    pub fn from_us(us: usize) -> Self {
+        Self {
+            sec: us / USEC_PER_SEC,
+            usec: us % USEC_PER_SEC
+        }
+    }
+}
+
+/// Returns the value of `time` CSR, in clock ticks.
+pub fn get_time() -> usize {
+    time::read()
+}
+
+/// Returns the time since boot, in milliseconds.
+pub fn get_time_ms() -> usize {
+    time::read() / (CLOCK_FREQ / MSEC_PER_SEC)
+}
+
+/// Returns the time since boot, in microseconds.
+pub fn get_time_us() -> usize {
+    time::read() / (CLOCK_FREQ / USEC_PER_SEC)
+}
+
+/// Triggers the next timer interrupt after a time slice.
+pub fn set_next_trigger() {
+    set_timer(get_time() + CLOCK_FREQ * TIME_SLICE_MS / MSEC_PER_SEC);
+}
+
+/// A blocked process, which is woken up once the time reaches `expire_ms`.
+struct Sleeper {
+    expire_ms: usize,
+    process: Arc<ProcessControlBlock>
+}
+
+impl PartialEq for Sleeper {
+    fn eq(&self, other: &Self) -> bool {
+        self.expire_ms == other.expire_ms
+    }
+}
+
+impl Eq for Sleeper {}
+
+impl PartialOrd for Sleeper {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Sleeper {
+    /// The earliest sleeper is the greatest, so that it is on top of the max-heap.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.expire_ms.cmp(&self.expire_ms)
+    }
+}
+
+lazy_static! {
+    static ref SLEEPERS: UPCell<BinaryHeap<Sleeper>> = unsafe { UPCell::new(BinaryHeap::new()) };
+}
+
+/// Wakes up `process` once the time reaches `expire_ms`. The process should be blocked
+/// right after, as it is added back to the ready queue then.
+pub fn add_sleeper(expire_ms: usize, process: Arc<ProcessControlBlock>) {
+    SLEEPERS.borrow_mut().push(Sleeper { expire_ms, process });
+}
+
+/// Wakes up the sleepers whose time is up.
+pub fn wake_sleepers() {
+    let now = get_time_ms();
+    let mut sleepers = SLEEPERS.borrow_mut();
+    while sleepers.peek().is_some_and(|sleeper| sleeper.expire_ms <= now) {
+        wakeup_process(sleepers.pop().unwrap().process);
+    }
+}
+
+pub fn has_sleepers() -> bool {
+    !SLEEPERS.borrow_mut().is_empty()
+}
@@ -5,18 +5,27 @@ use riscv::register::sstatus::{self, Sstatus, SPP};
 pub struct TrapContext {
     pub regs: [usize; 32],
     pub sstatus: Sstatus,
-    pub sepc: usize
+    pub sepc: usize,
+    /// Token of the kernel space, which `__alltraps` switches to.
+    pub kernel_satp: usize,
+    /// Kernel stack top of the app, which `__alltraps` switches to.
+    pub kernel_sp: usize,
+    /// Address of `trap_handler` in the kernel space.
+    pub trap_handler: usize
 }
 
 impl TrapContext {
-    pub fn new(entry: usize, sp: usize) -> Self {
+    pub fn new(entry: usize, sp: usize, kernel_satp: usize, kernel_sp: usize, trap_handler: usize) -> Self {
         let mut status = sstatus::read();
         status.set_spp(SPP::User);
 
         let mut ctx = Self {
             regs: [0usize; 32],
             sstatus: status,
-            sepc: entry
+            sepc: entry,
+            kernel_satp,
+            kernel_sp,
+            trap_handler
         };
         ctx.set_stack_pointer(sp);
 
@@ -1,13 +1,18 @@
 use crate::{
-    error, error_print, warn, warn_print,
+    error,
     syscall::*,
-    batch::run_next_app
+    task::{
+        exit_current_and_run_next, suspend_current_and_run_next,
+        current_app_name, current_trap_cx, current_user_token
+    },
+    timer::{set_next_trigger, wake_sleepers},
+    mm::{TRAMPOLINE, TRAP_CONTEXT}
 };
-use core::arch::global_asm;
+use core::arch::{asm, global_asm};
 use riscv::register::{
     mtvec::TrapMode,
     scause::{self, Trap, Exception, Interrupt},
-    stval, stvec
+    sie, stval, stvec
 };
 
 // Include section.
@@ -19,63 +24,121 @@ pub use ctx::TrapContext;
 global_asm!(include_str!("trap.S"));
 
 pub fn init() {
-    unsafe extern "C" {
-        fn __alltraps();
-    }
+    set_kernel_trap_entry();
+}
+
+/// Traps from the kernel itself are not expected, so they are caught here.
+fn set_kernel_trap_entry() {
+    unsafe { stvec::write(trap_from_kernel as usize, TrapMode::Direct) }
+}
 
-    unsafe { stvec::write(__alltraps as usize, TrapMode::Direct) }
+/// Traps from user mode enter `__alltraps` through the trampoline,
+/// which is mapped at the same address in every address space.
+fn set_user_trap_entry() {
+    unsafe { stvec::write(TRAMPOLINE, TrapMode::Direct) }
 }
 
-fn handle_exception(ctx: &mut TrapContext, exc: Exception) -> &mut TrapContext {
+/// Enables supervisor timer interrupt, which drives the preemptive scheduling.
+pub fn enable_timer_interrupt() {
+    unsafe { sie::set_stimer() }
+}
+
+fn handle_exception(ctx: &mut TrapContext, exc: Exception) {
     use scause::Exception::*;
 
-    match exc {
+    let exit_code = match exc {
         UserEnvCall => {
             ctx.sepc += 4;
-            ctx[10] = syscall(ctx[17], [ctx[10], ctx[11], ctx[12]]) as usize;
+            let result = syscall(ctx[17], [ctx[10], ctx[11], ctx[12], ctx[13], ctx[14], ctx[15]]);
+            // `exec` may have replaced the trap context, so it is fetched again.
+            current_trap_cx()[10] = result as usize;
 
-            return ctx;
+            return;
         },
-        StoreFault | StorePageFault => {
-            error!("[kernel] PageFault in application, kernel killed it.");
+        StoreFault | StorePageFault | LoadFault | LoadPageFault
+        | InstructionFault | InstructionPageFault => {
+            error!("[kernel] PageFault in application {}, kernel killed it.", current_app_name());
+            -2
         },
         IllegalInstruction => {
-            error!("[kernel] IllegalInstruction in application, kernel killed it.");
+            error!("[kernel] IllegalInstruction in application {}, kernel killed it.", current_app_name());
+            -3
         },
         _ => {
-            error_print!("Unsupported trap: ");
-            warn_print!("Exception({:?}), tval: {:?}", exc, stval::read());
-            error!(".");
-            warn!("Kernel skips the exception as it is not implemented.");
+            error!(
+                "[kernel] Unsupported exception {:?} in application {}, scause = {:#x}, stval = {:#x}, kernel killed it.",
+                exc, current_app_name(), scause::read().bits(), stval::read()
+            );
+            -1
         }
-    }
+    };
 
-    run_next_app()
-} 
+    exit_current_and_run_next(exit_code)
+}
 
-fn handle_interrupt(_ctx: &mut TrapContext, int: Interrupt) -> &mut TrapContext {
+fn handle_interrupt(int: Interrupt) {
     use scause::Interrupt::*;
 
     match int {
-        UserTimer => unimplemented!("There is expected to be a interval switch between processes."),
+        SupervisorTimer => {
+            // Current time slice is used up, so the task is preempted.
+            set_next_trigger();
+            wake_sleepers();
+            suspend_current_and_run_next();
+
+            return;
+        },
         _ => {
-            error_print!("Unsupported trap: ");
-            warn_print!("Interrupt({:?}), tval: {:?}", int, stval::read());
-            error!(".");
-            warn!("Kernel skips the exception as it is not implemented.");
+            error!(
+                "[kernel] Unsupported interrupt {:?} in application {}, scause = {:#x}, stval = {:#x}, kernel killed it.",
+                int, current_app_name(), scause::read().bits(), stval::read()
+            );
         }
     }
 
-    run_next_app()
+    exit_current_and_run_next(-1)
 }
 
 #[unsafe(no_mangle)]
-pub fn trap_handler(ctx: &mut TrapContext) -> &mut TrapContext {
+pub fn trap_handler() -> ! {
+    set_kernel_trap_entry();
+    let ctx = current_trap_cx();
     let cause = scause::read();
     let trap = cause.cause();
 
     match trap {
         Trap::Exception(exc) => handle_exception(ctx, exc),
-        Trap::Interrupt(int) => handle_interrupt(ctx, int)
+        Trap::Interrupt(int) => handle_interrupt(int)
     }
-}
\ No newline at end of file
+
+    trap_return()
+}
+
+/// Returns to user mode through `__restore` in the trampoline,
+/// which switches to the address space of current app.
+#[unsafe(no_mangle)]
+pub fn trap_return() -> ! {
+    set_user_trap_entry();
+    let trap_cx_ptr = TRAP_CONTEXT;
+    let user_satp = current_user_token();
+
+    unsafe extern "C" {
+        fn __alltraps();
+        fn __restore();
+    }
+
+    // `__restore` has to be addressed in the trampoline instead of where it is linked.
+    let restore_va = __restore as usize - __alltraps as usize + TRAMPOLINE;
+    unsafe {asm! {
+        "fence.i",
+        "jr {restore_va}",
+        restore_va = in(reg) restore_va,
+        in("a0") trap_cx_ptr,
+        in("a1") user_satp,
+        options(noreturn)
+    }}
+}
+
+fn trap_from_kernel() -> ! {
+    panic!("Unsupported trap from kernel: {:?}, stval = {:#x}.", scause::read().cause(), stval::read());
+}
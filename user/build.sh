@@ -10,11 +10,9 @@ build() {
     for file in $files; do
         length=${#file}
         # Redirect to the target elf file by slicing.
+        # The kernel loads the elf file directly, so no stripping is needed.
         target=${out_dir}${file:0:length - 3}
 
-        # Then do the strip work.
-        rust-objcopy --strip-all $target -O binary ${target}.bin
-
         if [ $1 == 1 ]
         then
             qemu-riscv64 ${target}
@@ -8,7 +8,7 @@
 extern crate user;
 
 #[unsafe(no_mangle)]
-fn main() -> i32 {
+fn main(_argc: usize, _argv: &[&str]) -> i32 {
     debug!("Task 0.");
 
     println!("Hello, world!");
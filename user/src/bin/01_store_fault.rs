@@ -9,7 +9,7 @@ use core::ptr::null_mut;
 extern crate user;
 
 #[unsafe(no_mangle)]
-fn main() -> i32 {
+fn main(_argc: usize, _argv: &[&str]) -> i32 {
     debug!("Task 1.");
     info!("This application trys to write value on a null pointer.");
     info!("The kernel will terminate this undefined behaviour.");
@@ -3,6 +3,7 @@
 #![feature(custom_test_frameworks)]
 #![test_runner(user::test_runner)]
 #![reexport_test_harness_main = "test_main"]
+use user::get_time;
 
 #[macro_use]
 extern crate user;
@@ -13,11 +14,12 @@ const STEP: usize = 100000;
 const MOD: u32 = 10007;
 
 #[unsafe(no_mangle)]
-fn main() -> i32 {
+fn main(_argc: usize, _argv: &[&str]) -> i32 {
     debug!("Task 2.");
     info!("This application do some regular arithmatic problems.");
     info!("It should work fine.");
 
+    let start = get_time().unwrap();
     let mut pow = [0u32; SIZE];
     let mut index: usize = 0;
     pow[index] = 1;
@@ -30,6 +32,7 @@ fn main() -> i32 {
         }
     }
     println!("Test power OK!");
+    println!("Time elapsed: {}ms.", get_time().unwrap() - start);
     
     0
 }
\ No newline at end of file
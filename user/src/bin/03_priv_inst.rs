@@ -9,7 +9,7 @@ use core::arch::asm;
 extern crate user;
 
 #[unsafe(no_mangle)]
-fn main() -> i32 {
+fn main(_argc: usize, _argv: &[&str]) -> i32 {
     debug!("Task 3.");
     info_print!("The kernel would instantly kill this application, ");
     info!("as it trys to run priviledged instructions in user mode.");
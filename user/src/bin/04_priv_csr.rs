@@ -9,7 +9,7 @@ use riscv::register::sstatus::{self, SPP};
 extern crate user;
 
 #[unsafe(no_mangle)]
-fn main() -> i32 {
+fn main(_argc: usize, _argv: &[&str]) -> i32 {
     debug!("Task 4.");
     info_print!("The kernel would instantly kill this application, ");
     info!("as it trys to access priviledged CSR in user mode.");
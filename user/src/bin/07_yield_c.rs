@@ -0,0 +1,30 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(user::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+use user::yield_;
+
+#[macro_use]
+extern crate user;
+
+const WIDTH: usize = 10;
+const HEIGHT: usize = 5;
+
+#[unsafe(no_mangle)]
+fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    debug!("Task 7.");
+    info!("This application prints lines of 'C', and yields the CPU after each line.");
+    info!("Its output should interleave with other yielding applications.");
+
+    for i in 0..HEIGHT {
+        for _ in 0..WIDTH {
+            print!("C");
+        }
+        println!(" [{}/{}]", i + 1, HEIGHT);
+        yield_();
+    }
+    println!("Test yield_c OK!");
+
+    0
+}
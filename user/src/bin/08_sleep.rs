@@ -0,0 +1,30 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(user::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+use user::{get_time, sleep};
+
+#[macro_use]
+extern crate user;
+
+const PERIOD_MS: usize = 100;
+
+#[unsafe(no_mangle)]
+fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    debug!("Task 8.");
+    info!("This application sleeps for {}ms, and checks the time elapsed.", PERIOD_MS);
+
+    let start = get_time().unwrap();
+    sleep(PERIOD_MS).expect("Failed to sleep.");
+    let elapsed = get_time().unwrap() - start;
+
+    println!("Time elapsed: {}ms.", elapsed);
+    if elapsed < PERIOD_MS {
+        error!("Woke up too early.");
+        return -1;
+    }
+    println!("Test sleep OK!");
+
+    0
+}
@@ -0,0 +1,54 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(user::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+use user::{Error, exec, exit, fork, getpid, wait, waitpid};
+
+#[macro_use]
+extern crate user;
+
+const MAX_CHILD: usize = 8;
+
+#[unsafe(no_mangle)]
+fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    debug!("Task 9.");
+    info!("This application forks {} workers, and waits for all of them.", MAX_CHILD);
+
+    for i in 0..MAX_CHILD {
+        let pid = fork().expect("Failed to fork.");
+        if pid == 0 {
+            println!("Worker {} runs in process {}.", i, getpid());
+            exit(100 + i as i32);
+        }
+        println!("Forked worker {} as process {}.", i, pid);
+    }
+
+    let mut exit_code = 0;
+    for _ in 0..MAX_CHILD {
+        let Ok(pid) = wait(&mut exit_code) else {
+            error!("Failed to wait for a worker.");
+            return -1;
+        };
+        println!("Process {} exited with code {}.", pid, exit_code);
+    }
+    if wait(&mut exit_code) != Err(Error::Child) {
+        error!("Found an extra worker.");
+        return -1;
+    }
+
+    // Replaces a child with another app, and waits for it specifically.
+    let pid = fork().expect("Failed to fork.");
+    if pid == 0 {
+        let err = exec("00_hello_world", &["00_hello_world"]).unwrap_err();
+        error!("Failed to exec 00_hello_world: {:?}.", err);
+        exit(-1);
+    }
+    if waitpid(pid as isize, &mut exit_code) != Ok(pid) || exit_code != 0 {
+        error!("Exec test failed with code {}.", exit_code);
+        return -1;
+    }
+    println!("Test fork OK!");
+
+    0
+}
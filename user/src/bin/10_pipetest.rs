@@ -0,0 +1,71 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(user::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+use user::{Error, SEEK_SET, close, exit, fork, lseek, pipe, read, wait, write};
+
+#[macro_use]
+extern crate user;
+
+const MESSAGE: &[u8] = b"Hello, pipe!";
+
+#[unsafe(no_mangle)]
+fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    debug!("Task 10.");
+    info!("This application sends a message to its child through a pipe.");
+
+    let mut fds = [0usize; 2];
+    pipe(&mut fds).expect("Failed to create a pipe.");
+    let [read_fd, write_fd] = fds;
+
+    if fork().expect("Failed to fork.") == 0 {
+        close(write_fd).unwrap();
+
+        // Reads until EOF, which arrives once the parent closes its write end.
+        let mut buffer = [0u8; 32];
+        let mut len = 0;
+        loop {
+            let count = read(read_fd, &mut buffer[len..]).expect("Failed to read from the pipe.");
+            if count == 0 {
+                break;
+            }
+            len += count;
+        }
+
+        if &buffer[..len] != MESSAGE {
+            error!("Received a wrong message.");
+            exit(-1);
+        }
+        println!("Received the message from the parent.");
+        exit(0);
+    }
+
+    close(read_fd).unwrap();
+    write(write_fd, MESSAGE).expect("Failed to write to the pipe.");
+    close(write_fd).unwrap();
+
+    let mut exit_code = 0;
+    wait(&mut exit_code).expect("Failed to wait for the child.");
+    if exit_code != 0 {
+        error!("Child exited with code {}.", exit_code);
+        return -1;
+    }
+
+    // A pipe has no offset, and cannot be written once its read end is closed.
+    pipe(&mut fds).expect("Failed to create a pipe.");
+    let [read_fd, write_fd] = fds;
+    if lseek(read_fd, 0, SEEK_SET) != Err(Error::SPipe) {
+        error!("Seeked a pipe.");
+        return -1;
+    }
+    close(read_fd).unwrap();
+    if write(write_fd, MESSAGE) != Err(Error::Pipe) {
+        error!("Wrote to a pipe without a read end.");
+        return -1;
+    }
+    close(write_fd).unwrap();
+    println!("Test pipe OK!");
+
+    0
+}
@@ -0,0 +1,57 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(user::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+use user::{
+    O_APPEND, O_CREAT, O_RDONLY, O_TRUNC, O_WRONLY, SEEK_END, SEEK_SET,
+    close, fstat, lseek, open, read, write
+};
+
+#[macro_use]
+extern crate user;
+
+const PATH: &str = "filetest";
+const MESSAGE: &[u8] = b"Hello, file!";
+const SUFFIX: &[u8] = b" Again!";
+
+#[unsafe(no_mangle)]
+fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    debug!("Task 11.");
+    info!("This application writes a file, and reads it back.");
+
+    let fd = open(PATH, O_CREAT | O_WRONLY | O_TRUNC).expect("Failed to create the file.");
+    write(fd, MESSAGE).expect("Failed to write the file.");
+    close(fd).unwrap();
+
+    // Appended bytes go to the end, even though the offset is moved back.
+    let fd = open(PATH, O_WRONLY | O_APPEND).expect("Failed to open the file for appending.");
+    lseek(fd, 0, SEEK_SET).unwrap();
+    write(fd, SUFFIX).expect("Failed to append to the file.");
+    close(fd).unwrap();
+
+    let fd = open(PATH, O_RDONLY).expect("Failed to open the file for reading.");
+    let stat = fstat(fd).expect("Failed to get the status of the file.");
+    if !stat.is_file() || stat.nlink != 1 {
+        error!("Wrong status of the file: {:?}.", stat);
+        return -1;
+    }
+
+    let mut buffer = [0u8; 32];
+    let len = read(fd, &mut buffer).expect("Failed to read the file.");
+    if &buffer[..MESSAGE.len()] != MESSAGE || &buffer[MESSAGE.len()..len] != SUFFIX {
+        error!("Read a wrong content.");
+        return -1;
+    }
+
+    let offset = lseek(fd, -(SUFFIX.len() as isize), SEEK_END).unwrap();
+    let len = read(fd, &mut buffer).unwrap();
+    if offset != MESSAGE.len() || &buffer[..len] != SUFFIX {
+        error!("Read a wrong content after seeking.");
+        return -1;
+    }
+    close(fd).unwrap();
+    println!("Test file OK!");
+
+    0
+}
@@ -0,0 +1,94 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(user::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+use user::{
+    DIRENT_BUFFER_SIZE, Error, O_CREAT, O_RDONLY, O_WRONLY,
+    close, dir_entries, fstat, getdents, link, mkdir, open, read, rmdir, unlink, write
+};
+
+#[macro_use]
+extern crate user;
+
+const DIR: &str = "/dirtest";
+const SUBDIR: &str = "/dirtest/sub";
+const FILE: &str = "/dirtest/sub/file";
+const LINK: &str = "/dirtest/link";
+const MESSAGE: &[u8] = b"Hello, directory!";
+
+#[unsafe(no_mangle)]
+fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    debug!("Task 12.");
+    info!("This application links a file across directories, lists and removes them.");
+
+    mkdir(DIR).expect("Failed to create the directory.");
+    mkdir(SUBDIR).expect("Failed to create the nested directory.");
+    if mkdir(SUBDIR) != Err(Error::Exist) {
+        error!("Created a directory twice.");
+        return -1;
+    }
+
+    let fd = open(FILE, O_CREAT | O_WRONLY).expect("Failed to create the file.");
+    write(fd, MESSAGE).expect("Failed to write the file.");
+    close(fd).unwrap();
+    link(FILE, LINK).expect("Failed to link the file.");
+    if link(SUBDIR, "/dirtest/sublink") != Err(Error::Perm) {
+        error!("Linked a directory.");
+        return -1;
+    }
+
+    // `.`, `..` and `sub` link to the directory.
+    let fd = open(DIR, O_RDONLY).expect("Failed to open the directory.");
+    let stat = fstat(fd).unwrap();
+    if !stat.is_dir() || stat.nlink != 3 {
+        error!("Wrong status of the directory: {:?}.", stat);
+        return -1;
+    }
+    let mut buffer = [0u8; DIRENT_BUFFER_SIZE];
+    let len = getdents(fd, &mut buffer).expect("Failed to read the directory.");
+    let expected = [(".", true), ("..", true), ("sub", true), ("link", false)];
+    if !dir_entries(&buffer[..len]).map(|entry| (entry.name, entry.is_dir())).eq(expected) {
+        error!("Listed wrong entries.");
+        return -1;
+    }
+    if getdents(fd, &mut buffer) != Ok(0) {
+        error!("Listed entries after the end.");
+        return -1;
+    }
+    if read(fd, &mut buffer) != Err(Error::IsDir) {
+        error!("Read a directory as a file.");
+        return -1;
+    }
+    close(fd).unwrap();
+
+    if rmdir(SUBDIR) != Err(Error::NotEmpty) || unlink(SUBDIR) != Err(Error::IsDir) {
+        error!("Removed a directory which is not empty, or by unlink.");
+        return -1;
+    }
+
+    // The file stays readable through the open descriptor after both links are removed.
+    unlink(FILE).expect("Failed to unlink the file.");
+    let fd = open(LINK, O_RDONLY).expect("Failed to open the link.");
+    if fstat(fd).unwrap().nlink != 1 {
+        error!("Wrong number of links after unlinking.");
+        return -1;
+    }
+    unlink(LINK).expect("Failed to unlink the link.");
+    let len = read(fd, &mut buffer).expect("Failed to read the unlinked file.");
+    if &buffer[..len] != MESSAGE {
+        error!("Read a wrong content.");
+        return -1;
+    }
+    close(fd).unwrap();
+    if open(LINK, O_RDONLY) != Err(Error::NoEnt) {
+        error!("Opened a removed file.");
+        return -1;
+    }
+
+    rmdir(SUBDIR).expect("Failed to remove the nested directory.");
+    rmdir(DIR).expect("Failed to remove the directory.");
+    println!("Test directory OK!");
+
+    0
+}
@@ -0,0 +1,38 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(user::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+use core::slice;
+use user::{Error, STDOUT, write};
+
+#[macro_use]
+extern crate user;
+
+/// Buffers which the kernel must refuse as `(address, length)`.
+const BAD_BUFFERS: [(usize, usize); 3] = [
+    // Not mapped at all.
+    (0, 1),
+    // Above the user half, where the low bits alias the page of the app itself.
+    (0x80_0001_0000, 1),
+    // Crossing the end of the user half.
+    (0x3f_ffff_fff0, 0x20)
+];
+
+#[unsafe(no_mangle)]
+fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    debug!("Task 13.");
+    info!("This application passes bad buffers to the kernel, which should fail with EFAULT.");
+
+    for (addr, len) in BAD_BUFFERS {
+        // The slice is only handed to the kernel, and never touched here.
+        let buffer = unsafe { slice::from_raw_parts(addr as *const u8, len) };
+        if write(STDOUT, buffer) != Err(Error::Fault) {
+            error!("Accepted a bad buffer at {:#x}.", addr);
+            return -1;
+        }
+    }
+    println!("Test bad pointer OK!");
+
+    0
+}
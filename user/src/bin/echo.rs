@@ -0,0 +1,29 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(user::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+#[macro_use]
+extern crate user;
+
+/// Prints the arguments separated by spaces, or without the trailing newline if `-n` comes first.
+#[unsafe(no_mangle)]
+fn main(_argc: usize, argv: &[&str]) -> i32 {
+    let (newline, args) = match argv.get(1) {
+        Some(&"-n") => (false, &argv[2..]),
+        _ => (true, argv.get(1..).unwrap_or_default())
+    };
+
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 {
+            print!(" ");
+        }
+        print!("{}", arg);
+    }
+    if newline {
+        println!();
+    }
+
+    0
+}
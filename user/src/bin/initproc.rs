@@ -0,0 +1,29 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(user::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+use user::{exec, fork, wait, yield_};
+
+#[macro_use]
+extern crate user;
+
+/// Spawns the shell, and then reaps every zombie adopted from its dead parent forever.
+#[unsafe(no_mangle)]
+fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    if fork() == Ok(0) {
+        let err = exec("user_shell", &["user_shell"]).unwrap_err();
+        error!("[initproc] Failed to exec user_shell: {:?}.", err);
+        return -1;
+    }
+
+    loop {
+        let mut exit_code = 0;
+        let Ok(pid) = wait(&mut exit_code) else {
+            // No child at all, so there is nothing to reap for now.
+            yield_();
+            continue;
+        };
+        info!("[initproc] Released a zombie process, pid = {}, exit_code = {}.", pid, exit_code);
+    }
+}
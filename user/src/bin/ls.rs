@@ -0,0 +1,66 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(user::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+use user::{DIRENT_BUFFER_SIZE, O_RDONLY, Result, close, dir_entries, fstat, getdents, open};
+
+#[macro_use]
+extern crate user;
+
+/// Lists the entries of each directory in the arguments, or of the root directory if there is
+/// none, where directories end with `/`. A file in the arguments is printed as it is.
+#[unsafe(no_mangle)]
+fn main(_argc: usize, argv: &[&str]) -> i32 {
+    let paths = match argv.get(1..) {
+        Some(paths) if !paths.is_empty() => paths,
+        _ => &["/"]
+    };
+
+    let mut exit_code = 0;
+    for (i, path) in paths.iter().enumerate() {
+        if paths.len() > 1 {
+            if i > 0 {
+                println!();
+            }
+            println!("{}:", path);
+        }
+        if let Err(err) = list(path) {
+            error!("Cannot access {}: {:?}.", path, err);
+            exit_code = -1;
+        }
+    }
+
+    exit_code
+}
+
+fn list(path: &str) -> Result<()> {
+    let fd = open(path, O_RDONLY)?;
+
+    let result = print_entries(fd, path);
+    close(fd)?;
+
+    result
+}
+
+fn print_entries(fd: usize, path: &str) -> Result<()> {
+    if !fstat(fd)?.is_dir() {
+        println!("{}", path);
+        return Ok(());
+    }
+
+    let mut buffer = [0u8; DIRENT_BUFFER_SIZE];
+    loop {
+        let len = getdents(fd, &mut buffer)?;
+        if len == 0 {
+            return Ok(());
+        }
+        for entry in dir_entries(&buffer[..len]).filter(|entry| entry.name != "." && entry.name != "..") {
+            if entry.is_dir() {
+                println!("{}/", entry.name);
+            } else {
+                println!("{}", entry.name);
+            }
+        }
+    }
+}
@@ -0,0 +1,198 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(user::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+use core::str;
+use user::{
+    Error, STDIN, STDOUT, O_RDONLY, O_WRONLY, O_CREAT, O_TRUNC,
+    close, dup2, exec, exit, fork, getchar, open, pipe, waitpid
+};
+
+#[macro_use]
+extern crate user;
+
+const LF: u8 = 0x0a;
+const CR: u8 = 0x0d;
+const BS: u8 = 0x08;
+const DL: u8 = 0x7f;
+/// Longest command line accepted.
+const MAX_LINE: usize = 127;
+/// Most commands connected by pipes in a line.
+const MAX_CMDS: usize = 8;
+/// Most arguments of a command, including the app name.
+const MAX_ARGS: usize = 16;
+
+/// A parsed command, whose arguments are slices of the command line.
+#[derive(Clone, Copy)]
+struct Command<'a> {
+    args: [&'a str; MAX_ARGS],
+    num_args: usize,
+    input: Option<&'a str>,
+    output: Option<&'a str>
+}
+
+impl<'a> Command<'a> {
+    const EMPTY: Self = Self { args: [""; MAX_ARGS], num_args: 0, input: None, output: None };
+
+    /// Parses whitespace-separated arguments, where `< file` and `> file` redirect the input
+    /// and output. Returns `None` if there is no app name, a file name is missing,
+    /// or there are too many arguments.
+    fn parse(cmd: &'a str) -> Option<Self> {
+        let mut command = Self::EMPTY;
+        let mut tokens = cmd.split_ascii_whitespace();
+        while let Some(token) = tokens.next() {
+            match token {
+                "<" => command.input = Some(tokens.next()?),
+                ">" => command.output = Some(tokens.next()?),
+                _ => {
+                    if command.num_args == MAX_ARGS {
+                        return None;
+                    }
+                    command.args[command.num_args] = token;
+                    command.num_args += 1;
+                }
+            }
+        }
+
+        (command.num_args > 0).then_some(command)
+    }
+
+    fn name(&self) -> &'a str {
+        self.args[0]
+    }
+}
+
+/// Reads command lines, runs each in child processes and reports their exit codes.
+/// Commands may be connected by pipes like `a | b`, and take arguments and redirections
+/// like `a arg < in > out`.
+#[unsafe(no_mangle)]
+fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    println!("Rust user shell");
+
+    let mut line = [0u8; MAX_LINE];
+    let mut len = 0;
+    print!(">> ");
+    loop {
+        match getchar() {
+            LF | CR => {
+                println!();
+                if len > 0 {
+                    // Only printable ASCII characters are accepted, so the line is always valid UTF-8.
+                    run(str::from_utf8(&line[..len]).unwrap());
+                    len = 0;
+                }
+                print!(">> ");
+            },
+            BS | DL => {
+                if len > 0 {
+                    // Moves back, erases the character on screen and moves back again.
+                    print!("{0} {0}", BS as char);
+                    len -= 1;
+                }
+            },
+            byte => {
+                if len < MAX_LINE && (byte.is_ascii_graphic() || byte == b' ') {
+                    print!("{}", byte as char);
+                    line[len] = byte;
+                    len += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Runs the commands in `line`, where the output of each is piped to the input of the next.
+fn run(line: &str) {
+    let mut cmds = [Command::EMPTY; MAX_CMDS];
+    let mut num_cmds = 0;
+    for cmd in line.split('|') {
+        let Some(command) = Command::parse(cmd).filter(|_| num_cmds < MAX_CMDS) else {
+            error!("Invalid command line.");
+            return;
+        };
+        cmds[num_cmds] = command;
+        num_cmds += 1;
+    }
+    let cmds = &cmds[..num_cmds];
+
+    let mut pipes = [[0usize; 2]; MAX_CMDS - 1];
+    for i in 0..num_cmds - 1 {
+        if let Err(err) = pipe(&mut pipes[i]) {
+            error!("Failed to create a pipe: {:?}.", err);
+            close_pipes(&pipes[..i]);
+            return;
+        }
+    }
+    let pipes = &pipes[..num_cmds - 1];
+
+    let mut pids = [0usize; MAX_CMDS];
+    let mut num_pids = 0;
+    for (i, command) in cmds.iter().enumerate() {
+        let pid = match fork() {
+            Ok(pid) => pid,
+            Err(err) => {
+                error!("Failed to fork: {:?}.", err);
+                break;
+            }
+        };
+        if pid == 0 {
+            if i > 0 {
+                dup2(pipes[i - 1][0], STDIN).unwrap();
+            }
+            if i < num_cmds - 1 {
+                dup2(pipes[i][1], STDOUT).unwrap();
+            }
+            // Only the duplicated ends are kept, so that readers see EOF once the writers exit.
+            close_pipes(pipes);
+
+            // Redirections take precedence over pipes.
+            if let Some(input) = command.input {
+                redirect(input, O_RDONLY, STDIN);
+            }
+            if let Some(output) = command.output {
+                redirect(output, O_WRONLY | O_CREAT | O_TRUNC, STDOUT);
+            }
+
+            let err = exec_command(command);
+            error!("Error when executing {}: {:?}.", command.name(), err);
+            exit(-4);
+        }
+        pids[num_pids] = pid;
+        num_pids += 1;
+    }
+    close_pipes(pipes);
+
+    for &pid in pids[..num_pids].iter() {
+        let mut exit_code = 0;
+        let exit_pid = waitpid(pid as isize, &mut exit_code);
+        assert_eq!(Ok(pid), exit_pid);
+        println!("Shell: Process {} exited with code {}", pid, exit_code);
+    }
+}
+
+fn close_pipes(pipes: &[[usize; 2]]) {
+    for &[read_fd, write_fd] in pipes {
+        close(read_fd).unwrap();
+        close(write_fd).unwrap();
+    }
+}
+
+/// Opens `path` with `flags` in place of `fd`, or exits if it cannot be opened.
+fn redirect(path: &str, flags: u32, fd: usize) {
+    match open(path, flags) {
+        Ok(file_fd) => {
+            dup2(file_fd, fd).unwrap();
+            close(file_fd).unwrap();
+        },
+        Err(err) => {
+            error!("Failed to open {}: {:?}.", path, err);
+            exit(-4);
+        }
+    }
+}
+
+/// Runs the app of `command` in the current process, and only returns on failure.
+fn exec_command(command: &Command) -> Error {
+    exec(command.name(), &command.args[..command.num_args]).unwrap_err()
+}
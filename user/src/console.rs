@@ -1,13 +1,11 @@
-use core::fmt::{Write, Result as FmtResult, Arguments};
-use crate::write;
-
-const STDOUT: usize = 1;
+use core::fmt::{Write, Result as FmtResult, Error as FmtError, Arguments};
+use crate::{STDOUT, write};
 
 struct Stdout;
 
 impl Write for Stdout {
     fn write_str(&mut self, s: &str) -> FmtResult {
-        write(STDOUT, s.as_bytes());
+        write(STDOUT, s.as_bytes()).map_err(|_| FmtError)?;
 
         Ok(())
     }
@@ -0,0 +1,82 @@
+/// Errors returned by syscalls, which are decoded from negative errno values in `a0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// `EPERM`: the operation is not permitted, e.g. linking a directory.
+    Perm,
+    /// `ENOENT`: no such file or app.
+    NoEnt,
+    /// `E2BIG`: the arguments of `exec` are too many or too long.
+    TooBig,
+    /// `ENOEXEC`: the file is not a valid executable.
+    NoExec,
+    /// `EBADF`: bad file descriptor, or one opened without the required access.
+    BadF,
+    /// `ECHILD`: no child matches.
+    Child,
+    /// `EAGAIN`: the resource is temporarily unavailable, and the call should be retried.
+    Again,
+    /// `EFAULT`: bad address in user space.
+    Fault,
+    /// `EEXIST`: the path exists already.
+    Exist,
+    /// `ENOTDIR`: a component of the path is not a directory.
+    NotDir,
+    /// `EISDIR`: the path is a directory, where a file is required.
+    IsDir,
+    /// `EINVAL`: invalid argument.
+    Inval,
+    /// `EMFILE`: the process has too many open files.
+    MFile,
+    /// `ENOSPC`: no space left on the device.
+    NoSpc,
+    /// `ESPIPE`: the file cannot be seeked, e.g. a pipe or the console.
+    SPipe,
+    /// `EPIPE`: the pipe has no read end left.
+    Pipe,
+    /// `ENAMETOOLONG`: a name in the path is too long.
+    NameTooLong,
+    /// `ENOSYS`: the syscall is not implemented.
+    NoSys,
+    /// `ENOTEMPTY`: the directory to be removed is not empty.
+    NotEmpty,
+    /// An errno value unknown to this crate.
+    Unknown(isize)
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+impl Error {
+    pub fn from_errno(errno: isize) -> Self {
+        match errno {
+            1 => Self::Perm,
+            2 => Self::NoEnt,
+            7 => Self::TooBig,
+            8 => Self::NoExec,
+            9 => Self::BadF,
+            10 => Self::Child,
+            11 => Self::Again,
+            14 => Self::Fault,
+            17 => Self::Exist,
+            20 => Self::NotDir,
+            21 => Self::IsDir,
+            22 => Self::Inval,
+            24 => Self::MFile,
+            28 => Self::NoSpc,
+            29 => Self::SPipe,
+            32 => Self::Pipe,
+            36 => Self::NameTooLong,
+            38 => Self::NoSys,
+            39 => Self::NotEmpty,
+            _ => Self::Unknown(errno)
+        }
+    }
+}
+
+/// Converts the return value of a syscall into a `Result`.
+pub(crate) fn check(ret: isize) -> Result<usize> {
+    if ret < 0 {
+        Err(Error::from_errno(-ret))
+    } else {
+        Ok(ret as usize)
+    }
+}
@@ -3,28 +3,154 @@
 #![feature(linkage, custom_test_frameworks)]
 #![test_runner(crate::test_runner)]
 #![reexport_test_harness_main = "test_main"]
+use core::{ffi::CStr, ptr};
+use error::check;
 use syscall::*;
 
 #[macro_use]
 pub mod console;
+mod error;
 mod syscall;
 mod lang_items;
 
+pub use error::{Error, Result};
 pub use lang_items::{handle_panic, test_runner};
 
+pub const STDIN: usize = 0;
+pub const STDOUT: usize = 1;
+
+/// Most arguments passed to `main`, and the rest are dropped. [`exec`] accepts as many.
+const MAX_ARGS: usize = 32;
+/// Total size of the arguments accepted by [`exec`], counting a `\0` after each.
+const MAX_ARGS_SIZE: usize = 1024;
+/// Longest path accepted by the wrappers of file syscalls.
+pub const MAX_PATH: usize = 255;
+
+/// Opens for reading only.
+pub const O_RDONLY: u32 = 0;
+/// Opens for writing only.
+pub const O_WRONLY: u32 = 1 << 0;
+/// Opens for both reading and writing.
+pub const O_RDWR: u32 = 1 << 1;
+/// Creates the file if it does not exist.
+pub const O_CREAT: u32 = 1 << 6;
+/// Truncates the file to empty when it is opened for writing.
+pub const O_TRUNC: u32 = 1 << 9;
+/// Writes to the end of the file, wherever the offset is.
+pub const O_APPEND: u32 = 1 << 10;
+
+/// Seeks from the start of the file.
+pub const SEEK_SET: usize = 0;
+/// Seeks from the current offset.
+pub const SEEK_CUR: usize = 1;
+/// Seeks from the end of the file.
+pub const SEEK_END: usize = 2;
+
+/// File type bits of [`Stat::mode`] for a directory.
+pub const S_IFDIR: u32 = 0o040000;
+/// File type bits of [`Stat::mode`] for a regular file.
+pub const S_IFREG: u32 = 0o100000;
+
+/// Type of a [`DirEntry`] which is a directory.
+pub const DT_DIR: u8 = 4;
+/// Type of a [`DirEntry`] which is a regular file.
+pub const DT_REG: u8 = 8;
+/// Size of a buffer of [`getdents`] which fits any entry.
+pub const DIRENT_BUFFER_SIZE: usize = 512;
+
+/// Time since boot, which shares the layout with `TimeVal` in the kernel.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TimeVal {
+    pub sec: usize,
+    pub usec: usize
+}
+
+/// Status of a file, which shares the layout with `Stat` in the kernel.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Stat {
+    pub dev: u64,
+    /// Inode number, which is unique in the filesystem.
+    pub ino: u64,
+    pub mode: u32,
+    /// Number of hard links to the inode.
+    pub nlink: u32,
+    pad: [u64; 7]
+}
+
+impl Stat {
+    pub fn is_dir(&self) -> bool {
+        self.mode & S_IFDIR == S_IFDIR
+    }
+
+    pub fn is_file(&self) -> bool {
+        self.mode & S_IFREG == S_IFREG
+    }
+}
+
+/// An entry of a directory, which is decoded from a `linux_dirent64` record.
+#[derive(Debug, Clone, Copy)]
+pub struct DirEntry<'a> {
+    /// Inode number, which is unique in the filesystem.
+    pub ino: u64,
+    /// One of the `DT_*` constants.
+    pub type_: u8,
+    pub name: &'a str
+}
+
+impl DirEntry<'_> {
+    pub fn is_dir(&self) -> bool {
+        self.type_ == DT_DIR
+    }
+}
+
+/// Decodes the records filled by [`getdents`] in `buf`.
+pub fn dir_entries(buf: &[u8]) -> impl Iterator<Item = DirEntry<'_>> {
+    let mut rest = buf;
+    core::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+
+        // `d_ino`, `d_off`, `d_reclen` and `d_type` are followed by the NUL-terminated name.
+        let ino = u64::from_ne_bytes(rest[0..8].try_into().unwrap());
+        let record_len = u16::from_ne_bytes(rest[16..18].try_into().unwrap()) as usize;
+        let type_ = rest[18];
+        let name = CStr::from_bytes_until_nul(&rest[19..record_len])
+            .ok()?
+            .to_str()
+            .ok()?;
+        rest = &rest[record_len..];
+
+        Some(DirEntry { ino, type_, name })
+    })
+}
+
+/// The kernel passes `argc` and the null-terminated `argv` in `a0` and `a1`,
+/// which point to NUL-terminated strings on the user stack.
 #[unsafe(no_mangle)]
 #[unsafe(link_section = ".text.entry")]
-pub extern "C" fn _start() -> ! {
+pub extern "C" fn _start(argc: usize, argv: usize) -> ! {
     clear_bss();
 
-    exit(main());
+    // The arguments stay on the stack until exit, since `_start` never returns.
+    let mut args = [""; MAX_ARGS];
+    let argc = argc.min(MAX_ARGS);
+    for (i, arg) in args[..argc].iter_mut().enumerate() {
+        let arg_ptr = unsafe { (argv as *const usize).add(i).read() };
+        let arg_str = unsafe { CStr::from_ptr(arg_ptr as *const _) };
+        *arg = arg_str.to_str().expect("Arguments must be valid UTF-8.");
+    }
+
+    exit(main(argc, &args[..argc]));
 
     unreachable!("Unreachable code after system exit return.");
 }
 
 #[linkage = "weak"]
 #[unsafe(no_mangle)]
-fn main() -> i32 {
+fn main(_argc: usize, _argv: &[&str]) -> i32 {
     panic!("No such main function.")
 }
 
@@ -39,10 +165,184 @@ fn clear_bss() {
     }
 }
 
-pub fn write(fd: usize, buf: &[u8]) -> isize {
-    sys_write(fd, buf)
+/// Duplicates `fd` to the lowest free descriptor, which is returned.
+pub fn dup(fd: usize) -> Result<usize> {
+    check(sys_dup(fd))
+}
+
+/// Duplicates `old_fd` to `new_fd`, which is closed first if it is open.
+pub fn dup2(old_fd: usize, new_fd: usize) -> Result<usize> {
+    check(sys_dup2(old_fd, new_fd))
+}
+
+/// Runs `f` with a copy of `s` which ends with `\0`, as the kernel expects. Fails with
+/// [`Error::NameTooLong`] if `s` is longer than [`MAX_PATH`], or [`Error::Inval`] if it has a `\0`.
+fn with_cstr<T>(s: &str, f: impl FnOnce(&CStr) -> Result<T>) -> Result<T> {
+    if s.len() > MAX_PATH {
+        return Err(Error::NameTooLong);
+    }
+
+    let mut buffer = [0u8; MAX_PATH + 1];
+    buffer[..s.len()].copy_from_slice(s.as_bytes());
+    let cstr = CStr::from_bytes_with_nul(&buffer[..=s.len()]).map_err(|_| Error::Inval)?;
+
+    f(cstr)
+}
+
+/// Opens the file at `path`, and returns its descriptor.
+/// `flags` is a combination of the `O_*` constants.
+pub fn open(path: &str, flags: u32) -> Result<usize> {
+    with_cstr(path, |path| check(sys_openat(AT_FDCWD, path, flags)))
+}
+
+/// Creates a directory at `path`.
+pub fn mkdir(path: &str) -> Result<usize> {
+    with_cstr(path, |path| check(sys_mkdirat(AT_FDCWD, path, 0)))
+}
+
+/// Adds a hard link at `new_path` to the file at `old_path`.
+pub fn link(old_path: &str, new_path: &str) -> Result<usize> {
+    with_cstr(old_path, |old_path| {
+        with_cstr(new_path, |new_path| check(sys_linkat(AT_FDCWD, old_path, AT_FDCWD, new_path, 0)))
+    })
+}
+
+/// Removes the file at `path`. The file is freed once it has no link left
+/// and it is closed everywhere.
+pub fn unlink(path: &str) -> Result<usize> {
+    with_cstr(path, |path| check(sys_unlinkat(AT_FDCWD, path, 0)))
+}
+
+/// Removes the empty directory at `path`.
+pub fn rmdir(path: &str) -> Result<usize> {
+    with_cstr(path, |path| check(sys_unlinkat(AT_FDCWD, path, AT_REMOVEDIR)))
+}
+
+/// Reads entries of the directory opened as `fd` into `buf`, and returns the number of bytes
+/// read, which is 0 at the end. Decode them with [`dir_entries`].
+pub fn getdents(fd: usize, buf: &mut [u8]) -> Result<usize> {
+    check(sys_getdents64(fd, buf))
+}
+
+pub fn close(fd: usize) -> Result<usize> {
+    check(sys_close(fd))
+}
+
+/// Moves the offset of `fd` by `offset` from the position given by `whence`,
+/// which is one of the `SEEK_*` constants. Returns the new offset.
+pub fn lseek(fd: usize, offset: isize, whence: usize) -> Result<usize> {
+    check(sys_lseek(fd, offset, whence))
+}
+
+/// Returns the status of the file opened as `fd`.
+pub fn fstat(fd: usize) -> Result<Stat> {
+    let mut stat = Stat::default();
+    check(sys_fstat(fd, &mut stat))?;
+
+    Ok(stat)
+}
+
+/// Creates a pipe, and stores the descriptors of its read end and write end into `pipe`.
+pub fn pipe(pipe: &mut [usize; 2]) -> Result<usize> {
+    check(sys_pipe(pipe))
+}
+
+/// Blocks until some bytes are read into `buf`, and returns the number of bytes read.
+pub fn read(fd: usize, buf: &mut [u8]) -> Result<usize> {
+    check(sys_read(fd, buf))
+}
+
+/// Blocks until a byte is read from the console.
+pub fn getchar() -> u8 {
+    let mut byte = [0u8; 1];
+    read(STDIN, &mut byte).expect("Failed to read from stdin.");
+
+    byte[0]
+}
+
+/// Returns the number of bytes written.
+pub fn write(fd: usize, buf: &[u8]) -> Result<usize> {
+    check(sys_write(fd, buf))
 }
 
 pub fn exit(exit_code: i32) -> isize {
     sys_exit(exit_code)
 }
+
+pub fn yield_() -> isize {
+    sys_yield()
+}
+
+/// Returns the time since boot in milliseconds.
+pub fn get_time() -> Result<usize> {
+    let mut time = TimeVal::default();
+    check(sys_get_time(&mut time, 0))?;
+
+    Ok(time.sec * 1000 + time.usec / 1000)
+}
+
+/// Blocks for at least `period_ms` milliseconds, and the kernel runs other apps meanwhile.
+pub fn sleep(period_ms: usize) -> Result<usize> {
+    check(sys_sleep(period_ms))
+}
+
+pub fn getpid() -> usize {
+    sys_getpid() as usize
+}
+
+/// Returns the pid of the child in the parent, and 0 in the child.
+pub fn fork() -> Result<usize> {
+    check(sys_fork())
+}
+
+/// Runs the app in the file at `path` in the current process, with `args` as its `argv`.
+/// Fails with [`Error::TooBig`] if there are more than 32 arguments or they are too long in total,
+/// or [`Error::Inval`] if one of them has a `\0`. It never returns on success.
+pub fn exec(path: &str, args: &[&str]) -> Result<usize> {
+    if args.len() > MAX_ARGS {
+        return Err(Error::TooBig);
+    }
+
+    // Every argument is copied into the buffer with a trailing `\0`.
+    let mut buffer = [0u8; MAX_ARGS_SIZE];
+    let mut offsets = [0usize; MAX_ARGS];
+    let mut start = 0;
+    for (i, arg) in args.iter().enumerate() {
+        let end = start + arg.len() + 1;
+        if end > MAX_ARGS_SIZE {
+            return Err(Error::TooBig);
+        }
+        if arg.contains('\0') {
+            return Err(Error::Inval);
+        }
+        buffer[start..end - 1].copy_from_slice(arg.as_bytes());
+        offsets[i] = start;
+        start = end;
+    }
+    let mut arg_ptrs = [ptr::null(); MAX_ARGS + 1];
+    for (arg_ptr, &offset) in arg_ptrs.iter_mut().zip(&offsets[..args.len()]) {
+        *arg_ptr = buffer[offset..].as_ptr();
+    }
+
+    with_cstr(path, |path| check(sys_exec(path, &arg_ptrs[..=args.len()])))
+}
+
+/// Waits for any child to exit, and returns its pid.
+/// Fails with [`Error::Child`] if there is no child.
+pub fn wait(exit_code: &mut i32) -> Result<usize> {
+    waitpid(-1, exit_code)
+}
+
+/// Waits for the child with given pid to exit, and returns its pid.
+/// Fails with [`Error::Child`] if there is no such child.
+pub fn waitpid(pid: isize, exit_code: &mut i32) -> Result<usize> {
+    loop {
+        match check(sys_waitpid(pid, exit_code as *mut i32)) {
+            // The child is still running.
+            Err(Error::Again) => {
+                yield_();
+            },
+            result => return result
+        }
+    }
+}
@@ -1,7 +1,28 @@
-use core::arch::asm;
+use core::{arch::asm, ffi::CStr};
+use crate::{Stat, TimeVal};
 
+const SYSCALL_DUP: usize = 24;
+const SYSCALL_MKDIRAT: usize = 34;
+const SYSCALL_UNLINKAT: usize = 35;
+const SYSCALL_LINKAT: usize = 37;
+const SYSCALL_OPENAT: usize = 56;
+const SYSCALL_CLOSE: usize = 57;
+const SYSCALL_PIPE: usize = 59;
+const SYSCALL_GETDENTS64: usize = 61;
+const SYSCALL_LSEEK: usize = 62;
+const SYSCALL_READ: usize = 63;
 const SYSCALL_WRITE: usize = 64;
+const SYSCALL_FSTAT: usize = 80;
 const SYSCALL_EXIT: usize = 93;
+const SYSCALL_YIELD: usize = 124;
+const SYSCALL_GET_TIME: usize = 169;
+const SYSCALL_GETPID: usize = 172;
+const SYSCALL_FORK: usize = 220;
+const SYSCALL_EXEC: usize = 221;
+const SYSCALL_WAITPID: usize = 260;
+// Syscalls of this kernel alone, which are out of the range of Linux.
+const SYSCALL_DUP2: usize = 1000;
+const SYSCALL_SLEEP: usize = 1001;
 
 fn syscall(id: usize, args: [usize; 3]) -> isize {
     let ret: isize;
@@ -16,10 +37,116 @@ fn syscall(id: usize, args: [usize; 3]) -> isize {
     ret
 }
 
+/// Same as `syscall`, for the syscalls taking more than 3 arguments.
+fn syscall6(id: usize, args: [usize; 6]) -> isize {
+    let ret: isize;
+    unsafe { asm!{
+        "ecall",
+        inlateout("x10") args[0] => ret,
+        in("x11") args[1],
+        in("x12") args[2],
+        in("x13") args[3],
+        in("x14") args[4],
+        in("x15") args[5],
+        in("x17") id
+    }}
+
+    ret
+}
+
+pub fn sys_dup2(old_fd: usize, new_fd: usize) -> isize {
+    syscall(SYSCALL_DUP2, [old_fd, new_fd, 0])
+}
+
+pub fn sys_dup(fd: usize) -> isize {
+    syscall(SYSCALL_DUP, [fd, 0, 0])
+}
+
+/// Paths relative to this directory fd are resolved from the current working directory.
+pub const AT_FDCWD: isize = -100;
+
+pub fn sys_openat(dirfd: isize, path: &CStr, flags: u32) -> isize {
+    syscall(SYSCALL_OPENAT, [dirfd as usize, path.as_ptr() as usize, flags as usize])
+}
+
+/// Flag of `unlinkat` to remove a directory rather than a file.
+pub const AT_REMOVEDIR: u32 = 0x200;
+
+pub fn sys_mkdirat(dirfd: isize, path: &CStr, mode: u32) -> isize {
+    syscall(SYSCALL_MKDIRAT, [dirfd as usize, path.as_ptr() as usize, mode as usize])
+}
+
+pub fn sys_unlinkat(dirfd: isize, path: &CStr, flags: u32) -> isize {
+    syscall(SYSCALL_UNLINKAT, [dirfd as usize, path.as_ptr() as usize, flags as usize])
+}
+
+pub fn sys_linkat(old_dirfd: isize, old_path: &CStr, new_dirfd: isize, new_path: &CStr, flags: u32) -> isize {
+    syscall6(SYSCALL_LINKAT, [
+        old_dirfd as usize,
+        old_path.as_ptr() as usize,
+        new_dirfd as usize,
+        new_path.as_ptr() as usize,
+        flags as usize,
+        0
+    ])
+}
+
+pub fn sys_close(fd: usize) -> isize {
+    syscall(SYSCALL_CLOSE, [fd, 0, 0])
+}
+
+pub fn sys_pipe(pipe: &mut [usize; 2]) -> isize {
+    syscall(SYSCALL_PIPE, [pipe.as_mut_ptr() as usize, 0, 0])
+}
+
+pub fn sys_getdents64(fd: usize, buf: &mut [u8]) -> isize {
+    syscall(SYSCALL_GETDENTS64, [fd, buf.as_mut_ptr() as usize, buf.len()])
+}
+
+pub fn sys_lseek(fd: usize, offset: isize, whence: usize) -> isize {
+    syscall(SYSCALL_LSEEK, [fd, offset as usize, whence])
+}
+
+pub fn sys_read(fd: usize, buf: &mut [u8]) -> isize {
+    syscall(SYSCALL_READ, [fd, buf.as_mut_ptr() as usize, buf.len()])
+}
+
 pub fn sys_write(fd: usize, buf: &[u8]) -> isize {
     syscall(SYSCALL_WRITE, [fd, buf.as_ptr() as usize, buf.len()])
 }
 
+pub fn sys_fstat(fd: usize, stat: &mut Stat) -> isize {
+    syscall(SYSCALL_FSTAT, [fd, stat as *mut Stat as usize, 0])
+}
+
 pub fn sys_exit(code: i32) -> isize {
     syscall(SYSCALL_EXIT, [code as usize, 0, 0])
-}
\ No newline at end of file
+}
+
+pub fn sys_sleep(period_ms: usize) -> isize {
+    syscall(SYSCALL_SLEEP, [period_ms, 0, 0])
+}
+
+pub fn sys_yield() -> isize {
+    syscall(SYSCALL_YIELD, [0, 0, 0])
+}
+
+pub fn sys_get_time(time: &mut TimeVal, tz: usize) -> isize {
+    syscall(SYSCALL_GET_TIME, [time as *mut TimeVal as usize, tz, 0])
+}
+
+pub fn sys_getpid() -> isize {
+    syscall(SYSCALL_GETPID, [0, 0, 0])
+}
+
+pub fn sys_fork() -> isize {
+    syscall(SYSCALL_FORK, [0, 0, 0])
+}
+
+pub fn sys_exec(path: &CStr, args: &[*const u8]) -> isize {
+    syscall(SYSCALL_EXEC, [path.as_ptr() as usize, args.as_ptr() as usize, 0])
+}
+
+pub fn sys_waitpid(pid: isize, exit_code: *mut i32) -> isize {
+    syscall(SYSCALL_WAITPID, [pid as usize, exit_code as usize, 0])
+}